@@ -1,5 +1,6 @@
 use clap::Parser;
-use okx_connector::client::{OKXRestClient, OKXWebSocketClient};
+use okx_connector::client::{Environment, OKXRestClient, OKXWebSocketClient};
+use okx_connector::models::{WsEvent, WsMessage};
 use tokio::sync::mpsc;
 
 /// OKX Connector Demo - Order Book Example
@@ -33,6 +34,15 @@ struct Config {
     #[arg(short = 's', long, env = "OKX_SYMBOL", default_value = "BTC-USDT")]
     symbol: String,
 
+    /// Trading environment: "production" or "demo"
+    #[arg(
+        short = 'e',
+        long,
+        env = "OKX_ENVIRONMENT",
+        default_value = "production"
+    )]
+    environment: Environment,
+
     /// Number of WebSocket updates to display
     #[arg(short = 'u', long, env = "OKX_UPDATE_COUNT", default_value = "10")]
     update_count: usize,
@@ -103,11 +113,12 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     println!("   REST URL:  {}", config.rest_url);
     println!("   WS URL:    {}", config.ws_url);
     println!("   Symbol:    {}", config.symbol);
+    println!("   Env:       {}", config.environment);
     println!("   Updates:   {}", config.update_count);
 
     // Initialize REST client
     println!("\n📡 Connecting to OKX REST API...");
-    let rest_client = OKXRestClient::new(&config.rest_url)?;
+    let rest_client = OKXRestClient::new(&config.rest_url)?.with_environment(config.environment);
     println!("✅ Connected successfully!");
 
     // Fetch order book snapshot
@@ -131,9 +142,9 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                 println!(
                     "│ {:>3} │ {} │ {} │ {} │",
                     i + 1,
-                    format_price(*price),
-                    format_amount(*amount),
-                    format_total(*price, *amount)
+                    format_price(price.to_f64()),
+                    format_amount(amount.to_f64()),
+                    format_total(price.to_f64(), amount.to_f64())
                 );
             }
             println!(
@@ -142,13 +153,14 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
 
             // Calculate spread
             if let (Some((best_ask, _)), Some((best_bid, _))) =
-                (snapshot.asks.first(), snapshot.bids.first())
+                (snapshot.best_ask(), snapshot.best_bid())
             {
+                let (best_ask, best_bid) = (best_ask.to_f64(), best_bid.to_f64());
                 let spread = best_ask - best_bid;
                 let spread_pct = (spread / best_bid) * 100.0;
                 println!("\n💰 Market Spread:");
-                println!("   Best Ask:  {}", format_price(*best_ask));
-                println!("   Best Bid:  {}", format_price(*best_bid));
+                println!("   Best Ask:  {}", format_price(best_ask));
+                println!("   Best Bid:  {}", format_price(best_bid));
                 println!(
                     "   Spread:    {} ({:.4}%)",
                     format_price(spread),
@@ -169,9 +181,9 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                 println!(
                     "│ {:>3} │ {} │ {} │ {} │",
                     i + 1,
-                    format_price(*price),
-                    format_amount(*amount),
-                    format_total(*price, *amount)
+                    format_price(price.to_f64()),
+                    format_amount(amount.to_f64()),
+                    format_total(price.to_f64(), amount.to_f64())
                 );
             }
             println!(
@@ -186,7 +198,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
 
     // Initialize WebSocket client
     print_header("🔌 Connecting to WebSocket for Real-time Updates");
-    let ws_client = OKXWebSocketClient::new(&config.ws_url);
+    let ws_client = OKXWebSocketClient::new(&config.ws_url).with_environment(config.environment);
 
     // Create a channel for receiving WebSocket messages
     let (tx, mut rx) = mpsc::channel(100);
@@ -213,11 +225,24 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     for _ in 0..config.update_count {
         match rx.recv().await {
             Some(message) => {
-                // Parse the message to determine its type
-                if message.contains("\"event\":\"subscribe\"") {
-                    println!("✅ Subscription confirmed!");
-                    continue;
-                }
+                let push = match message {
+                    WsMessage::Event(WsEvent::Subscribe { .. }) => {
+                        println!("✅ Subscription confirmed!");
+                        continue;
+                    }
+                    WsMessage::Event(WsEvent::Error { code, msg, .. }) => {
+                        eprintln!("❌ Subscription error {}: {}", code, msg);
+                        continue;
+                    }
+                    WsMessage::Event(_) | WsMessage::Response(_) | WsMessage::Unknown(_) => {
+                        continue
+                    }
+                    WsMessage::Reconnected { attempts } => {
+                        println!("🔄 Reconnected after {} attempt(s)", attempts);
+                        continue;
+                    }
+                    WsMessage::Push(push) => push,
+                };
 
                 update_count += 1;
                 println!(
@@ -226,71 +251,69 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                 );
 
                 // Try to parse and display the update nicely
-                if let Ok(json) = serde_json::from_str::<serde_json::Value>(&message) {
-                    if let Some(data) = json.get("data").and_then(|d| d.as_array()) {
-                        for item in data {
-                            if let Some(action) = json.get("action").and_then(|a| a.as_str()) {
-                                println!("│ Action: {}", action);
-                            }
+                if let Ok(data) = push.decode::<serde_json::Value>() {
+                    for item in data {
+                        if let Some(action) = &push.action {
+                            println!("│ Action: {}", action);
+                        }
 
-                            if let Some(ts) = item.get("ts").and_then(|t| t.as_str()) {
-                                if let Ok(timestamp) = ts.parse::<u64>() {
-                                    println!("│ Time:   {}", format_timestamp(timestamp));
-                                }
+                        if let Some(ts) = item.get("ts").and_then(|t| t.as_str()) {
+                            if let Ok(timestamp) = ts.parse::<u64>() {
+                                println!("│ Time:   {}", format_timestamp(timestamp));
                             }
+                        }
 
-                            // Count asks and bids changes
-                            let asks_count = item
-                                .get("asks")
-                                .and_then(|a| a.as_array())
-                                .map(|a| a.len())
-                                .unwrap_or(0);
-                            let bids_count = item
-                                .get("bids")
-                                .and_then(|b| b.as_array())
-                                .map(|b| b.len())
-                                .unwrap_or(0);
-
-                            if asks_count > 0 || bids_count > 0 {
-                                println!("│ Changes: {} asks, {} bids", asks_count, bids_count);
-                            }
+                        // Count asks and bids changes
+                        let asks_count = item
+                            .get("asks")
+                            .and_then(|a| a.as_array())
+                            .map(|a| a.len())
+                            .unwrap_or(0);
+                        let bids_count = item
+                            .get("bids")
+                            .and_then(|b| b.as_array())
+                            .map(|b| b.len())
+                            .unwrap_or(0);
+
+                        if asks_count > 0 || bids_count > 0 {
+                            println!("│ Changes: {} asks, {} bids", asks_count, bids_count);
+                        }
 
-                            // Show first few price levels if available
-                            if let Some(asks) = item.get("asks").and_then(|a| a.as_array()) {
-                                if !asks.is_empty() {
-                                    println!("│ Sample Ask Updates:");
-                                    for (idx, ask) in asks.iter().take(3).enumerate() {
-                                        if let Some(arr) = ask.as_array() {
-                                            if arr.len() >= 2 {
-                                                let price = arr[0].as_str().unwrap_or("0");
-                                                let amount = arr[1].as_str().unwrap_or("0");
-                                                println!(
-                                                    "│   {}. Price: {}, Amount: {}",
-                                                    idx + 1,
-                                                    price,
-                                                    amount
-                                                );
-                                            }
+                        // Show first few price levels if available
+                        if let Some(asks) = item.get("asks").and_then(|a| a.as_array()) {
+                            if !asks.is_empty() {
+                                println!("│ Sample Ask Updates:");
+                                for (idx, ask) in asks.iter().take(3).enumerate() {
+                                    if let Some(arr) = ask.as_array() {
+                                        if arr.len() >= 2 {
+                                            let price = arr[0].as_str().unwrap_or("0");
+                                            let amount = arr[1].as_str().unwrap_or("0");
+                                            println!(
+                                                "│   {}. Price: {}, Amount: {}",
+                                                idx + 1,
+                                                price,
+                                                amount
+                                            );
                                         }
                                     }
                                 }
                             }
+                        }
 
-                            if let Some(bids) = item.get("bids").and_then(|b| b.as_array()) {
-                                if !bids.is_empty() {
-                                    println!("│ Sample Bid Updates:");
-                                    for (idx, bid) in bids.iter().take(3).enumerate() {
-                                        if let Some(arr) = bid.as_array() {
-                                            if arr.len() >= 2 {
-                                                let price = arr[0].as_str().unwrap_or("0");
-                                                let amount = arr[1].as_str().unwrap_or("0");
-                                                println!(
-                                                    "│   {}. Price: {}, Amount: {}",
-                                                    idx + 1,
-                                                    price,
-                                                    amount
-                                                );
-                                            }
+                        if let Some(bids) = item.get("bids").and_then(|b| b.as_array()) {
+                            if !bids.is_empty() {
+                                println!("│ Sample Bid Updates:");
+                                for (idx, bid) in bids.iter().take(3).enumerate() {
+                                    if let Some(arr) = bid.as_array() {
+                                        if arr.len() >= 2 {
+                                            let price = arr[0].as_str().unwrap_or("0");
+                                            let amount = arr[1].as_str().unwrap_or("0");
+                                            println!(
+                                                "│   {}. Price: {}, Amount: {}",
+                                                idx + 1,
+                                                price,
+                                                amount
+                                            );
                                         }
                                     }
                                 }
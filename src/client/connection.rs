@@ -0,0 +1,576 @@
+use crate::client::websocket_client::WebSocketError;
+use crate::models::trading::{
+    AmendRequest, CancelRequest, MassCancelAck, MassCancelRequest, OrderAck, OrderRequest,
+};
+use crate::models::ws_message::{ChannelArg, WsEvent, WsMessage, WsResponse};
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::sync::{Arc, OnceLock};
+use std::time::Duration;
+use tokio::sync::{mpsc, oneshot};
+use tokio::task::JoinHandle;
+
+/// Subscription operation sent in the `op` field
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum Op {
+    Subscribe,
+    Unsubscribe,
+}
+
+/// Request from a [`WsConnection`] handle to its connection task
+pub(crate) enum Command {
+    /// Change the subscribed channels
+    Subscription {
+        op: Op,
+        args: Vec<ChannelArg>,
+        reply: oneshot::Sender<Result<(), WebSocketError>>,
+    },
+    /// Send any other operation and wait for its response
+    Request {
+        op: String,
+        args: Vec<Value>,
+        reply: oneshot::Sender<Result<WsResponse, WebSocketError>>,
+    },
+    /// A handle stopped waiting for a request; forget the abandoned ones
+    Expire,
+}
+
+/// A request sent to OKX and waiting for its acknowledgements
+struct Pending {
+    op: Op,
+    args: Vec<ChannelArg>,
+    /// Acknowledgements still expected, one per argument
+    remaining: usize,
+    reply: oneshot::Sender<Result<(), WebSocketError>>,
+}
+
+/// Subscriptions of one logical connection, kept across reconnects, and
+/// the requests in flight on it
+pub(crate) struct Subscriptions {
+    /// Channels replayed whenever a new connection is established
+    args: Vec<ChannelArg>,
+    pending: HashMap<String, Pending>,
+    requests: HashMap<String, oneshot::Sender<Result<WsResponse, WebSocketError>>>,
+    next_id: u64,
+    commands: Option<mpsc::Receiver<Command>>,
+}
+
+impl Subscriptions {
+    pub(crate) fn new(args: Vec<ChannelArg>, commands: Option<mpsc::Receiver<Command>>) -> Self {
+        let mut subscriptions = Subscriptions {
+            args: Vec::with_capacity(args.len()),
+            pending: HashMap::new(),
+            requests: HashMap::new(),
+            next_id: 1,
+            commands,
+        };
+        subscriptions.add(&args);
+        subscriptions
+    }
+
+    /// Channels to subscribe to on a fresh connection
+    pub(crate) fn args(&self) -> &[ChannelArg] {
+        &self.args
+    }
+
+    /// Waits for the next command, or forever if there is no handle
+    pub(crate) async fn next_command(&mut self) -> Option<Command> {
+        match self.commands.as_mut() {
+            Some(commands) => {
+                let command = commands.recv().await;
+                if command.is_none() {
+                    self.commands = None;
+                }
+                command
+            }
+            None => std::future::pending().await,
+        }
+    }
+
+    /// Registers `command` and returns the request to send, if any
+    ///
+    /// The replayed set is updated straight away so that a reconnect while
+    /// a subscription is in flight resubscribes to the right channels.
+    /// Commands whose caller stopped waiting are dropped unsent.
+    pub(crate) fn request(&mut self, command: Command) -> Option<String> {
+        let id = self.next_id.to_string();
+        let message = match command {
+            // The caller already timed out, e.g. while the socket was down
+            Command::Subscription { reply, .. } if reply.is_closed() => return None,
+            Command::Request { reply, .. } if reply.is_closed() => return None,
+            Command::Subscription { args, reply, .. } if args.is_empty() => {
+                let _ = reply.send(Ok(()));
+                return None;
+            }
+            Command::Subscription { op, args, reply } => {
+                match op {
+                    Op::Subscribe => self.add(&args),
+                    Op::Unsubscribe => self.remove(&args),
+                }
+                let message = serde_json::json!({ "id": id, "op": op, "args": args });
+                let pending = Pending {
+                    op,
+                    remaining: args.len(),
+                    args,
+                    reply,
+                };
+                self.pending.insert(id.clone(), pending);
+                message
+            }
+            Command::Request { op, args, reply } => {
+                self.requests.insert(id.clone(), reply);
+                serde_json::json!({ "id": id, "op": op, "args": args })
+            }
+            Command::Expire => {
+                self.expire();
+                return None;
+            }
+        };
+        self.next_id += 1;
+        Some(message.to_string())
+    }
+
+    /// Correlates a message with the request that caused it
+    pub(crate) fn handle_message(&mut self, message: &WsMessage) {
+        match message {
+            WsMessage::Event(event) => self.handle_event(event),
+            WsMessage::Response(response) => {
+                if let Some(reply) = self.requests.remove(&response.id) {
+                    let _ = reply.send(Ok(response.clone()));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn handle_event(&mut self, event: &WsEvent) {
+        match event {
+            WsEvent::Subscribe { id: Some(id), .. } | WsEvent::Unsubscribe { id: Some(id), .. } => {
+                let Some(pending) = self.pending.get_mut(id) else {
+                    return;
+                };
+                pending.remaining = pending.remaining.saturating_sub(1);
+                if pending.remaining == 0 {
+                    if let Some(pending) = self.pending.remove(id) {
+                        let _ = pending.reply.send(Ok(()));
+                    }
+                }
+            }
+            WsEvent::Error {
+                id: Some(id),
+                code,
+                msg,
+                ..
+            } => {
+                let error = WebSocketError::Rejected {
+                    code: code.clone(),
+                    msg: msg.clone(),
+                };
+                let Some(pending) = self.pending.remove(id) else {
+                    if let Some(reply) = self.requests.remove(id) {
+                        let _ = reply.send(Err(error));
+                    }
+                    return;
+                };
+                if pending.op == Op::Subscribe {
+                    self.remove(&pending.args);
+                }
+                let _ = pending.reply.send(Err(error));
+            }
+            _ => {}
+        }
+    }
+
+    /// Forgets requests whose caller gave up waiting
+    ///
+    /// Abandoned subscriptions are dropped from the replayed set as if OKX
+    /// had rejected them; abandoned unsubscriptions stay removed.
+    fn expire(&mut self) {
+        self.requests.retain(|_, reply| !reply.is_closed());
+        let mut abandoned = Vec::new();
+        self.pending.retain(|_, pending| {
+            if !pending.reply.is_closed() {
+                return true;
+            }
+            if pending.op == Op::Subscribe {
+                abandoned.append(&mut pending.args);
+            }
+            false
+        });
+        self.remove(&abandoned);
+    }
+
+    /// Settles requests left unanswered by a connection that went away
+    ///
+    /// Unacknowledged subscriptions are dropped from the replayed set and
+    /// reported as failed; unsubscriptions succeed since a new connection
+    /// starts without them. Other operations fail, whether or not OKX
+    /// processed them.
+    pub(crate) fn connection_lost(&mut self) {
+        for (_, reply) in self.requests.drain() {
+            let _ = reply.send(Err(WebSocketError::ConnectionClosed));
+        }
+        for (_, pending) in self.pending.drain() {
+            let result = match pending.op {
+                Op::Subscribe => {
+                    self.args.retain(|arg| !pending.args.contains(arg));
+                    Err(WebSocketError::ConnectionClosed)
+                }
+                Op::Unsubscribe => Ok(()),
+            };
+            let _ = pending.reply.send(result);
+        }
+    }
+
+    fn add(&mut self, args: &[ChannelArg]) {
+        for arg in args {
+            if !self.args.contains(arg) {
+                self.args.push(arg.clone());
+            }
+        }
+    }
+
+    fn remove(&mut self, args: &[ChannelArg]) {
+        self.args.retain(|arg| !args.contains(arg));
+    }
+}
+
+/// Handle to a long-lived WebSocket connection
+///
+/// Created by [`OKXWebSocketClient::connect`](crate::client::OKXWebSocketClient::connect).
+/// Channels can be added and removed at any time; each call is sent as a
+/// single `op` message and resolves once OKX has acknowledged every
+/// argument, rejected the request, or the request timeout has passed. The
+/// connection is reconnected and its
+/// current subscriptions replayed as configured on the client, and it is
+/// closed when the handle is dropped.
+///
+/// On a private connection (see
+/// [`with_credentials`](crate::client::OKXWebSocketClient::with_credentials))
+/// orders can also be placed, amended and canceled. Each operation is sent
+/// with a unique `id` and resolves with OKX's response, an
+/// [`OrderRejected`](WebSocketError::OrderRejected) error carrying `sCode`
+/// and `sMsg`, or a timeout. An operation interrupted by a disconnection
+/// fails with [`ConnectionClosed`](WebSocketError::ConnectionClosed) even if
+/// OKX processed it; check the `orders` channel before retrying.
+pub struct WsConnection {
+    commands: mpsc::Sender<Command>,
+    task: JoinHandle<Result<(), WebSocketError>>,
+    request_timeout: Duration,
+    /// Error the connection task stopped on, when reconnecting cannot help
+    fatal: Arc<OnceLock<WebSocketError>>,
+}
+
+impl WsConnection {
+    pub(crate) fn new(
+        commands: mpsc::Sender<Command>,
+        task: JoinHandle<Result<(), WebSocketError>>,
+        request_timeout: Duration,
+        fatal: Arc<OnceLock<WebSocketError>>,
+    ) -> Self {
+        WsConnection {
+            commands,
+            task,
+            request_timeout,
+            fatal,
+        }
+    }
+
+    /// Subscribes to all `args` in one request
+    pub async fn subscribe(&self, args: Vec<ChannelArg>) -> Result<(), WebSocketError> {
+        self.subscription(Op::Subscribe, args).await
+    }
+
+    /// Unsubscribes from all `args` in one request
+    pub async fn unsubscribe(&self, args: Vec<ChannelArg>) -> Result<(), WebSocketError> {
+        self.subscription(Op::Unsubscribe, args).await
+    }
+
+    /// Places one order
+    pub async fn place_order(&self, order: OrderRequest) -> Result<OrderAck, WebSocketError> {
+        single(self.operation("order", vec![order]).await?)
+    }
+
+    /// Places up to 20 orders; each gets its own outcome
+    pub async fn place_orders(
+        &self,
+        orders: Vec<OrderRequest>,
+    ) -> Result<Vec<Result<OrderAck, WebSocketError>>, WebSocketError> {
+        acks(&self.operation("batch-orders", orders).await?)
+    }
+
+    /// Cancels one order
+    pub async fn cancel_order(&self, cancel: CancelRequest) -> Result<OrderAck, WebSocketError> {
+        single(self.operation("cancel-order", vec![cancel]).await?)
+    }
+
+    /// Cancels up to 20 orders; each gets its own outcome
+    pub async fn cancel_orders(
+        &self,
+        cancels: Vec<CancelRequest>,
+    ) -> Result<Vec<Result<OrderAck, WebSocketError>>, WebSocketError> {
+        acks(&self.operation("batch-cancel-orders", cancels).await?)
+    }
+
+    /// Amends the price or size of one order
+    pub async fn amend_order(&self, amend: AmendRequest) -> Result<OrderAck, WebSocketError> {
+        single(self.operation("amend-order", vec![amend]).await?)
+    }
+
+    /// Amends up to 20 orders; each gets its own outcome
+    pub async fn amend_orders(
+        &self,
+        amends: Vec<AmendRequest>,
+    ) -> Result<Vec<Result<OrderAck, WebSocketError>>, WebSocketError> {
+        acks(&self.operation("batch-amend-orders", amends).await?)
+    }
+
+    /// Cancels all MMP pending orders of an instrument family
+    pub async fn mass_cancel(
+        &self,
+        request: MassCancelRequest,
+    ) -> Result<MassCancelAck, WebSocketError> {
+        let response = self.operation("mass-cancel", vec![request]).await?;
+        if response.code != "0" {
+            return Err(rejected(&response));
+        }
+        response
+            .decode()?
+            .into_iter()
+            .next()
+            .ok_or_else(|| rejected(&response))
+    }
+
+    /// Sends operation `op` with `args` and waits for the response
+    ///
+    /// Fails with [`WebSocketError::RequestTimeout`] if no response arrives
+    /// in time, and with [`WebSocketError::Rejected`] if OKX answers with an
+    /// `error` event. A response with a nonzero `code` is returned as is.
+    pub async fn request(&self, op: &str, args: Vec<Value>) -> Result<WsResponse, WebSocketError> {
+        let (reply, response) = oneshot::channel();
+        let command = Command::Request {
+            op: op.to_string(),
+            args,
+            reply,
+        };
+        self.commands
+            .send(command)
+            .await
+            .map_err(|_| WebSocketError::ConnectionClosed)?;
+        match tokio::time::timeout(self.request_timeout, response).await {
+            Ok(result) => result.map_err(|_| WebSocketError::ConnectionClosed)?,
+            Err(_) => {
+                let _ = self.commands.send(Command::Expire).await;
+                Err(WebSocketError::RequestTimeout(self.request_timeout))
+            }
+        }
+    }
+
+    /// Returns `true` once the connection task has stopped
+    pub fn is_closed(&self) -> bool {
+        self.task.is_finished()
+    }
+
+    /// The error the connection stopped on, if reconnecting cannot fix it
+    ///
+    /// See [`WebSocketError::is_fatal`].
+    pub fn fatal_error(&self) -> Option<&WebSocketError> {
+        self.fatal.get()
+    }
+
+    async fn operation<T: Serialize>(
+        &self,
+        op: &str,
+        args: Vec<T>,
+    ) -> Result<WsResponse, WebSocketError> {
+        let args = args
+            .iter()
+            .map(serde_json::to_value)
+            .collect::<Result<_, _>>()?;
+        self.request(op, args).await
+    }
+
+    async fn subscription(&self, op: Op, args: Vec<ChannelArg>) -> Result<(), WebSocketError> {
+        let (reply, response) = oneshot::channel();
+        let command = Command::Subscription { op, args, reply };
+        self.commands
+            .send(command)
+            .await
+            .map_err(|_| WebSocketError::ConnectionClosed)?;
+        match tokio::time::timeout(self.request_timeout, response).await {
+            Ok(result) => result.map_err(|_| WebSocketError::ConnectionClosed)?,
+            Err(_) => {
+                let _ = self.commands.send(Command::Expire).await;
+                Err(WebSocketError::RequestTimeout(self.request_timeout))
+            }
+        }
+    }
+}
+
+fn rejected(response: &WsResponse) -> WebSocketError {
+    WebSocketError::Rejected {
+        code: response.code.clone(),
+        msg: response.msg.clone(),
+    }
+}
+
+/// Splits an order operation response into per-order outcomes
+///
+/// `code` is `"1"` when every item failed and `"2"` when only some did;
+/// either way the items carry their own `sCode` and `sMsg`.
+fn acks(response: &WsResponse) -> Result<Vec<Result<OrderAck, WebSocketError>>, WebSocketError> {
+    if !matches!(response.code.as_str(), "0" | "1" | "2") {
+        return Err(rejected(response));
+    }
+    let acks: Vec<OrderAck> = response.decode()?;
+    if acks.is_empty() {
+        return Err(rejected(response));
+    }
+    Ok(acks
+        .into_iter()
+        .map(|ack| {
+            if ack.is_success() {
+                Ok(ack)
+            } else {
+                Err(WebSocketError::OrderRejected {
+                    s_code: ack.s_code,
+                    s_msg: ack.s_msg,
+                })
+            }
+        })
+        .collect())
+}
+
+fn single(response: WsResponse) -> Result<OrderAck, WebSocketError> {
+    acks(&response)?
+        .into_iter()
+        .next()
+        .ok_or_else(|| rejected(&response))?
+}
+
+impl Drop for WsConnection {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(inst_ids: &[&str]) -> Vec<ChannelArg> {
+        inst_ids
+            .iter()
+            .map(|inst_id| ChannelArg::instrument("books", inst_id))
+            .collect()
+    }
+
+    fn command(
+        op: Op,
+        inst_ids: &[&str],
+    ) -> (Command, oneshot::Receiver<Result<(), WebSocketError>>) {
+        let (reply, response) = oneshot::channel();
+        let args = args(inst_ids);
+        (Command::Subscription { op, args, reply }, response)
+    }
+
+    #[test]
+    fn test_requests_are_correlated_with_acknowledgements() {
+        let mut subscriptions = Subscriptions::new(Vec::new(), None);
+
+        let (subscribe, mut response) = command(Op::Subscribe, &["BTC-USDT", "ETH-USDT"]);
+        let request = subscriptions.request(subscribe).unwrap();
+        assert_eq!(
+            request,
+            r#"{"args":[{"channel":"books","instId":"BTC-USDT"},{"channel":"books","instId":"ETH-USDT"}],"id":"1","op":"subscribe"}"#
+        );
+        assert_eq!(subscriptions.args().len(), 2);
+
+        let ack = |inst_id: &str| WsEvent::Subscribe {
+            arg: ChannelArg::instrument("books", inst_id),
+            id: Some("1".into()),
+            conn_id: None,
+        };
+        subscriptions.handle_event(&ack("BTC-USDT"));
+        assert!(response.try_recv().is_err());
+        subscriptions.handle_event(&ack("ETH-USDT"));
+        assert!(response.try_recv().unwrap().is_ok());
+
+        let (subscribe, mut response) = command(Op::Subscribe, &["BAD-PAIR"]);
+        subscriptions.request(subscribe).unwrap();
+        assert_eq!(subscriptions.args().len(), 3);
+        subscriptions.handle_event(&WsEvent::Error {
+            code: "60018".into(),
+            msg: "Wrong URL or channel".into(),
+            id: Some("2".into()),
+            conn_id: None,
+        });
+        assert!(matches!(
+            response.try_recv().unwrap(),
+            Err(WebSocketError::Rejected { code, .. }) if code == "60018"
+        ));
+        assert_eq!(subscriptions.args().len(), 2);
+
+        let (unsubscribe, mut response) = command(Op::Unsubscribe, &["BTC-USDT"]);
+        subscriptions.request(unsubscribe).unwrap();
+        assert_eq!(
+            subscriptions.args(),
+            [ChannelArg::instrument("books", "ETH-USDT")]
+        );
+        subscriptions.connection_lost();
+        assert!(response.try_recv().unwrap().is_ok());
+    }
+
+    #[test]
+    fn test_abandoned_subscription_is_rolled_back() {
+        let mut subscriptions = Subscriptions::new(args(&["BTC-USDT"]), None);
+
+        let (subscribe, response) = command(Op::Subscribe, &["ETH-USDT"]);
+        subscriptions.request(subscribe).unwrap();
+        assert_eq!(subscriptions.args().len(), 2);
+
+        drop(response);
+        assert!(subscriptions.request(Command::Expire).is_none());
+        assert!(subscriptions.pending.is_empty());
+        assert_eq!(subscriptions.args(), args(&["BTC-USDT"]));
+
+        let (subscribe, response) = command(Op::Subscribe, &["SOL-USDT"]);
+        drop(response);
+        assert!(subscriptions.request(subscribe).is_none());
+        assert!(subscriptions.pending.is_empty());
+        assert_eq!(subscriptions.args(), args(&["BTC-USDT"]));
+    }
+
+    #[test]
+    fn test_error_event_settles_operation() {
+        let mut subscriptions = Subscriptions::new(Vec::new(), None);
+        let request = |subscriptions: &mut Subscriptions| {
+            let (reply, response) = oneshot::channel();
+            let command = Command::Request {
+                op: "order".into(),
+                args: Vec::new(),
+                reply,
+            };
+            subscriptions.request(command).unwrap();
+            response
+        };
+
+        let mut response = request(&mut subscriptions);
+        subscriptions.handle_event(&WsEvent::Error {
+            code: "60013".into(),
+            msg: "Invalid args".into(),
+            id: Some("1".into()),
+            conn_id: None,
+        });
+        assert!(matches!(
+            response.try_recv().unwrap(),
+            Err(WebSocketError::Rejected { code, .. }) if code == "60013"
+        ));
+
+        drop(request(&mut subscriptions));
+        subscriptions.request(Command::Expire);
+        assert!(subscriptions.requests.is_empty());
+    }
+}
@@ -0,0 +1,125 @@
+use std::fmt;
+use std::str::FromStr;
+use url::Url;
+
+/// WebSocket hosts of the production environment
+const PRODUCTION_WS_HOSTS: [&str; 2] = ["ws.okx.com", "wsaws.okx.com"];
+
+/// WebSocket host of the demo trading environment
+const DEMO_WS_HOST: &str = "wspap.okx.com";
+
+/// OKX trading environment
+///
+/// Demo trading (paper trading) is served from the same REST host, with
+/// every request carrying the `x-simulated-trading: 1` header, and from a
+/// separate WebSocket host. Demo trading needs API keys created for it.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum Environment {
+    #[default]
+    Production,
+    Demo,
+}
+
+impl Environment {
+    pub const REST_URL: &'static str = "https://www.okx.com";
+
+    /// Returns `true` for demo trading
+    pub fn is_demo(&self) -> bool {
+        *self == Environment::Demo
+    }
+
+    /// URL of the public WebSocket endpoint
+    pub fn public_ws_url(&self) -> String {
+        format!("wss://{}:8443/ws/v5/public", self.ws_host())
+    }
+
+    /// URL of the private WebSocket endpoint, for account and order channels
+    pub fn private_ws_url(&self) -> String {
+        format!("wss://{}:8443/ws/v5/private", self.ws_host())
+    }
+
+    /// URL of the business WebSocket endpoint, e.g. for candles and `trades-all`
+    pub fn business_ws_url(&self) -> String {
+        format!("wss://{}:8443/ws/v5/business", self.ws_host())
+    }
+
+    fn ws_host(&self) -> &'static str {
+        match self {
+            Environment::Production => PRODUCTION_WS_HOSTS[0],
+            Environment::Demo => DEMO_WS_HOST,
+        }
+    }
+
+    /// Points an OKX WebSocket URL at this environment's host
+    ///
+    /// URLs on other hosts, such as a local test server, are returned as is.
+    pub(crate) fn ws_url(&self, url: &str) -> String {
+        let Ok(mut parsed) = Url::parse(url) else {
+            return url.to_string();
+        };
+        let is_okx = parsed
+            .host_str()
+            .is_some_and(|host| host == DEMO_WS_HOST || PRODUCTION_WS_HOSTS.contains(&host));
+        let already_here = match self {
+            Environment::Production => parsed
+                .host_str()
+                .is_some_and(|host| PRODUCTION_WS_HOSTS.contains(&host)),
+            Environment::Demo => parsed.host_str() == Some(DEMO_WS_HOST),
+        };
+        if !is_okx || already_here || parsed.set_host(Some(self.ws_host())).is_err() {
+            return url.to_string();
+        }
+        parsed.to_string()
+    }
+}
+
+impl FromStr for Environment {
+    type Err = String;
+
+    /// Accepts `production` (or `live`) and `demo` (or `paper`), in any case
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "production" | "live" => Ok(Environment::Production),
+            "demo" | "paper" => Ok(Environment::Demo),
+            _ => Err(format!("Unknown environment: {}", s)),
+        }
+    }
+}
+
+impl fmt::Display for Environment {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Environment::Production => f.write_str("production"),
+            Environment::Demo => f.write_str("demo"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ws_urls_follow_environment() {
+        let public = Environment::Production.public_ws_url();
+        assert_eq!(public, "wss://ws.okx.com:8443/ws/v5/public");
+        assert_eq!(
+            Environment::Demo.ws_url(&public),
+            "wss://wspap.okx.com:8443/ws/v5/public"
+        );
+        assert_eq!(
+            Environment::Production.ws_url("wss://wspap.okx.com:8443/ws/v5/private"),
+            Environment::Production.private_ws_url()
+        );
+        assert_eq!(
+            Environment::Production.ws_url("wss://wsaws.okx.com:8443/ws/v5/public"),
+            "wss://wsaws.okx.com:8443/ws/v5/public"
+        );
+        assert_eq!(
+            Environment::Demo.ws_url("ws://127.0.0.1:9000"),
+            "ws://127.0.0.1:9000"
+        );
+        assert_eq!("Paper".parse(), Ok(Environment::Demo));
+        assert!("staging".parse::<Environment>().is_err());
+    }
+}
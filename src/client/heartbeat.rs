@@ -0,0 +1,24 @@
+use std::time::Duration;
+
+/// Keepalive policy for a WebSocket connection
+///
+/// OKX closes connections that see no traffic for 30 seconds. When nothing
+/// has been received for `interval`, the client sends a literal `ping` text
+/// frame; if neither `pong` nor any other frame arrives within
+/// `pong_timeout`, the connection is treated as dead and reconnected.
+#[derive(Debug, Clone, PartialEq)]
+pub struct HeartbeatConfig {
+    /// Idle time after which a `ping` is sent, must stay below 30 seconds
+    pub interval: Duration,
+    /// Time allowed for the reply before the connection is dropped
+    pub pong_timeout: Duration,
+}
+
+impl Default for HeartbeatConfig {
+    fn default() -> Self {
+        HeartbeatConfig {
+            interval: Duration::from_secs(25),
+            pong_timeout: Duration::from_secs(5),
+        }
+    }
+}
@@ -0,0 +1,358 @@
+use crate::client::reconnect::ReconnectConfig;
+use crate::client::websocket_client::OKXWebSocketClient;
+use crate::models::orderbook::{OrderbookError, OrderbookUpdate, RawOrderbookData};
+use crate::models::response::ApiErrorKind;
+use crate::models::ws_message::{WsEvent, WsMessage};
+use crate::models::Orderbook;
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+use thiserror::Error;
+use tokio::sync::{broadcast, mpsc};
+use tokio::task::JoinHandle;
+
+/// Channel used when none is given: 400 levels, pushed every 100 ms
+pub const DEFAULT_BOOK_CHANNEL: &str = "books";
+
+#[derive(Error, Debug)]
+pub enum LiveOrderbookError {
+    #[error("Order book error: {0}")]
+    Orderbook(#[from] OrderbookError),
+    #[error("Failed to decode push: {0}")]
+    Decode(#[from] serde_json::Error),
+    #[error("Received an update before any snapshot")]
+    UpdateBeforeSnapshot,
+    #[error("Unknown book action: {0}")]
+    UnknownAction(String),
+    #[error("Subscription error {code}: {msg}")]
+    Subscription { code: String, msg: String },
+    #[error("WebSocket connection closed")]
+    ConnectionClosed,
+}
+
+impl LiveOrderbookError {
+    /// Returns `true` if resubscribing may succeed
+    ///
+    /// Subscription errors are permanent, such as an unknown instrument or
+    /// channel, unless OKX reports a rate limit or an internal error.
+    pub fn is_transient(&self) -> bool {
+        match self {
+            LiveOrderbookError::Subscription { code, .. } => {
+                matches!(code.as_str(), "60014" | "63999")
+                    || ApiErrorKind::from_code(code).is_retryable()
+            }
+            _ => true,
+        }
+    }
+}
+
+/// Change notification emitted by a [`LiveOrderbook`]
+#[derive(Debug, Clone, PartialEq)]
+pub enum OrderbookEvent {
+    /// A fresh snapshot replaced the book
+    Snapshot { ts: u64, seq_id: Option<i64> },
+    /// An incremental update was applied
+    Update { ts: u64, seq_id: Option<i64> },
+    /// The book was discarded and is being rebuilt from a new subscription
+    Resync { reason: String },
+    /// The book was discarded after a permanent error and will not be rebuilt
+    Stopped { reason: String },
+}
+
+/// Applies one message to `book`, returning the resulting change if any
+fn handle_message(
+    book: &mut Option<Orderbook>,
+    message: WsMessage,
+) -> Result<Option<OrderbookEvent>, LiveOrderbookError> {
+    let push = match message {
+        WsMessage::Push(push) => push,
+        WsMessage::Event(WsEvent::Error { code, msg, .. }) => {
+            return Err(LiveOrderbookError::Subscription { code, msg });
+        }
+        WsMessage::Event(_) | WsMessage::Response(_) | WsMessage::Unknown(_) => return Ok(None),
+        WsMessage::Reconnected { attempts } => {
+            *book = None;
+            return Ok(Some(OrderbookEvent::Resync {
+                reason: format!("reconnected after {} attempt(s)", attempts),
+            }));
+        }
+    };
+
+    // Depth-limited channels such as `books5` and `bbo-tbt` push full books
+    // without an `action`
+    match push.action.as_deref() {
+        None | Some("snapshot") => {
+            let raw: Vec<RawOrderbookData> = push.decode()?;
+            let raw = raw.into_iter().next().ok_or(OrderbookError::EmptyData)?;
+            let snapshot = Orderbook::from_raw(raw)?;
+            let event = OrderbookEvent::Snapshot {
+                ts: snapshot.ts,
+                seq_id: snapshot.seq_id,
+            };
+            *book = Some(snapshot);
+            Ok(Some(event))
+        }
+        Some("update") => {
+            let book = book
+                .as_mut()
+                .ok_or(LiveOrderbookError::UpdateBeforeSnapshot)?;
+            let updates: Vec<OrderbookUpdate> = push.decode()?;
+            for update in updates {
+                book.apply(update)?;
+            }
+            Ok(Some(OrderbookEvent::Update {
+                ts: book.ts,
+                seq_id: book.seq_id,
+            }))
+        }
+        Some(other) => Err(LiveOrderbookError::UnknownAction(other.to_string())),
+    }
+}
+
+/// Order book kept in sync with an OKX WebSocket book channel
+///
+/// A background task subscribes to the channel, builds the book from the
+/// `snapshot` push, applies `update` pushes with checksum and sequence
+/// validation, and on any error discards the book and resubscribes on a new
+/// connection to obtain a fresh snapshot, backing off exponentially while no
+/// snapshot arrives. A permanent subscription error, such as an unknown
+/// instrument, is reported as [`OrderbookEvent::Stopped`] and ends the task.
+/// The task also stops when the `LiveOrderbook` is dropped.
+pub struct LiveOrderbook {
+    book: Arc<RwLock<Option<Orderbook>>>,
+    events: broadcast::Sender<OrderbookEvent>,
+    task: JoinHandle<()>,
+}
+
+impl LiveOrderbook {
+    /// Starts maintaining the `books` channel for `symbol`
+    pub fn start(url: &str, symbol: &str) -> Self {
+        Self::start_with_channel(url, DEFAULT_BOOK_CHANNEL, symbol)
+    }
+
+    /// Starts maintaining `channel` (e.g. `books50-l2-tbt`) for `symbol`
+    ///
+    /// Channels that only push full books, such as `books5` and `bbo-tbt`,
+    /// replace the book on every push.
+    pub fn start_with_channel(url: &str, channel: &str, symbol: &str) -> Self {
+        Self::start_with_client(OKXWebSocketClient::new(url), channel, symbol)
+    }
+
+    /// Starts maintaining `channel` for `symbol` through a configured `client`
+    ///
+    /// The client's environment, heartbeat and reconnection policy apply to
+    /// every connection the book opens.
+    pub fn start_with_client(client: OKXWebSocketClient, channel: &str, symbol: &str) -> Self {
+        let book = Arc::new(RwLock::new(None));
+        let (events, _) = broadcast::channel(1024);
+        let task = tokio::spawn(run(
+            client,
+            channel.to_string(),
+            symbol.to_string(),
+            Arc::clone(&book),
+            events.clone(),
+        ));
+        LiveOrderbook { book, events, task }
+    }
+
+    /// Returns a copy of the current book, or `None` while (re)syncing
+    pub fn book(&self) -> Option<Orderbook> {
+        self.with_book(|book| book.cloned())
+    }
+
+    /// Runs `f` against the current book without copying it
+    pub fn with_book<R>(&self, f: impl FnOnce(Option<&Orderbook>) -> R) -> R {
+        let guard = self.book.read().unwrap_or_else(|e| e.into_inner());
+        f(guard.as_ref())
+    }
+
+    /// Returns a stream of change notifications
+    pub fn subscribe(&self) -> broadcast::Receiver<OrderbookEvent> {
+        self.events.subscribe()
+    }
+}
+
+impl Drop for LiveOrderbook {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+/// Delay before the first resubscription after the book was discarded
+const RESYNC_DELAY: Duration = Duration::from_secs(1);
+
+/// Longest delay between resubscriptions that yield no snapshot
+const MAX_RESYNC_DELAY: Duration = Duration::from_secs(60);
+
+async fn run(
+    client: OKXWebSocketClient,
+    channel: String,
+    symbol: String,
+    shared: Arc<RwLock<Option<Orderbook>>>,
+    events: broadcast::Sender<OrderbookEvent>,
+) {
+    let client = Arc::new(client);
+    let backoff = ReconnectConfig {
+        initial_delay: RESYNC_DELAY,
+        max_delay: MAX_RESYNC_DELAY,
+        ..Default::default()
+    };
+    let mut attempt = 0;
+    loop {
+        let (tx, mut rx) = mpsc::channel(1024);
+        let connection = {
+            let client = Arc::clone(&client);
+            let (channel, symbol) = (channel.clone(), symbol.clone());
+            tokio::spawn(async move { client.subscribe_to_channel(&channel, &symbol, tx).await })
+        };
+
+        let error = loop {
+            let Some(message) = rx.recv().await else {
+                break LiveOrderbookError::ConnectionClosed;
+            };
+            let result = {
+                let mut book = shared.write().unwrap_or_else(|e| e.into_inner());
+                handle_message(&mut book, message)
+            };
+            match result {
+                Ok(Some(event)) => {
+                    if matches!(event, OrderbookEvent::Snapshot { .. }) {
+                        attempt = 0;
+                    }
+                    let _ = events.send(event);
+                }
+                Ok(None) => {}
+                Err(e) => break e,
+            }
+        };
+
+        connection.abort();
+        *shared.write().unwrap_or_else(|e| e.into_inner()) = None;
+        let reason = error.to_string();
+        if !error.is_transient() {
+            let _ = events.send(OrderbookEvent::Stopped { reason });
+            return;
+        }
+        let _ = events.send(OrderbookEvent::Resync { reason });
+        attempt += 1;
+        tokio::time::sleep(backoff.delay(attempt)).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::testing::{accept, ws_server};
+
+    const SNAPSHOT: &str = r#"{"arg":{"channel":"books","instId":"BTC-USDT"},"action":"snapshot","data":[{"asks":[["3366.8","9","10","3"]],"bids":[["3366.1","7","0","3"]],"ts":"1597026383085","checksum":CHECKSUM,"prevSeqId":-1,"seqId":100}]}"#;
+
+    fn parse(text: &str) -> WsMessage {
+        WsMessage::parse(text).unwrap()
+    }
+
+    fn snapshot() -> String {
+        let checksum = crc32fast::hash(b"3366.1:7:3366.8:9") as i32;
+        SNAPSHOT.replace("CHECKSUM", &checksum.to_string())
+    }
+
+    #[test]
+    fn test_handle_message_snapshot_and_update() {
+        let mut book = None;
+        let subscribed = r#"{"event":"subscribe","arg":{"channel":"books","instId":"BTC-USDT"},"connId":"a4d3ae55"}"#;
+        assert!(handle_message(&mut book, parse(subscribed))
+            .unwrap()
+            .is_none());
+
+        let event = handle_message(&mut book, parse(&snapshot())).unwrap();
+        assert_eq!(
+            event,
+            Some(OrderbookEvent::Snapshot {
+                ts: 1597026383085,
+                seq_id: Some(100)
+            })
+        );
+
+        let checksum = crc32fast::hash(b"3366.1:7:3366.8:4") as i32;
+        let update = format!(
+            r#"{{"arg":{{"channel":"books","instId":"BTC-USDT"}},"action":"update","data":[{{"asks":[["3366.8","4","0","2"]],"bids":[],"ts":"1597026383090","checksum":{checksum},"prevSeqId":100,"seqId":101}}]}}"#
+        );
+        let event = handle_message(&mut book, parse(&update)).unwrap();
+        assert_eq!(
+            event,
+            Some(OrderbookEvent::Update {
+                ts: 1597026383090,
+                seq_id: Some(101)
+            })
+        );
+        assert_eq!(book.unwrap().best_ask().unwrap().1, "4".parse().unwrap());
+    }
+
+    #[test]
+    fn test_handle_message_rejects_gap_and_early_update() {
+        let mut book = None;
+        let update = r#"{"arg":{"channel":"books","instId":"BTC-USDT"},"action":"update","data":[{"asks":[],"bids":[],"ts":"1597026383090","prevSeqId":100,"seqId":101}]}"#;
+        assert!(matches!(
+            handle_message(&mut book, parse(update)),
+            Err(LiveOrderbookError::UpdateBeforeSnapshot)
+        ));
+
+        handle_message(&mut book, parse(&snapshot())).unwrap();
+        let gap = update.replace("\"prevSeqId\":100", "\"prevSeqId\":99");
+        assert!(matches!(
+            handle_message(&mut book, parse(&gap)),
+            Err(LiveOrderbookError::Orderbook(
+                OrderbookError::SequenceGap { .. }
+            ))
+        ));
+
+        handle_message(&mut book, parse(&snapshot())).unwrap();
+        let event = handle_message(&mut book, WsMessage::Reconnected { attempts: 2 }).unwrap();
+        assert!(matches!(event, Some(OrderbookEvent::Resync { .. })));
+        assert!(book.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_permanent_subscription_error_stops_the_book() {
+        let (listener, url) = ws_server().await;
+        tokio::spawn(async move {
+            use futures_util::{SinkExt, StreamExt};
+            use tokio_tungstenite::tungstenite::protocol::Message;
+            let mut ws = accept(&listener).await;
+            ws.next().await.unwrap().unwrap();
+            let error = r#"{"event":"error","code":"60018","msg":"Wrong URL or channel","connId":"a4d3ae55"}"#;
+            ws.send(Message::Text(error.into())).await.unwrap();
+            while let Some(Ok(_)) = ws.next().await {}
+        });
+
+        let live = LiveOrderbook::start(&url, "NOPE-USDT");
+        let mut events = live.subscribe();
+        assert!(matches!(
+            events.recv().await.unwrap(),
+            OrderbookEvent::Stopped { reason } if reason.contains("60018")
+        ));
+        assert!(live.book().is_none());
+
+        let busy = LiveOrderbookError::Subscription {
+            code: "60014".into(),
+            msg: "Requests too frequent".into(),
+        };
+        assert!(busy.is_transient());
+        assert!(LiveOrderbookError::ConnectionClosed.is_transient());
+    }
+
+    #[test]
+    fn test_handle_message_treats_push_without_action_as_snapshot() {
+        let mut book = None;
+        let push = |ask: &str| {
+            format!(
+                r#"{{"arg":{{"channel":"books5","instId":"BTC-USDT"}},"data":[{{"asks":[["{ask}","9","0","3"]],"bids":[["3366.1","7","0","3"]],"instId":"BTC-USDT","ts":"1597026383085","seqId":100}}]}}"#
+            )
+        };
+        for ask in ["3366.8", "3366.5"] {
+            let event = handle_message(&mut book, parse(&push(ask))).unwrap();
+            assert!(matches!(event, Some(OrderbookEvent::Snapshot { .. })));
+        }
+        let book = book.unwrap();
+        assert_eq!(book.best_ask().unwrap().0, "3366.5".parse().unwrap());
+        assert_eq!(book.asks.len(), 1);
+    }
+}
@@ -1,5 +1,25 @@
+pub mod auth;
+pub mod connection;
+pub mod environment;
+pub mod heartbeat;
+pub mod live_orderbook;
+pub mod pool;
+pub mod rate_limit;
+pub mod reconnect;
 pub mod rest_client;
+pub mod retry;
+#[cfg(test)]
+mod testing;
 pub mod websocket_client;
 
+pub use auth::Credentials;
+pub use connection::WsConnection;
+pub use environment::Environment;
+pub use heartbeat::HeartbeatConfig;
+pub use live_orderbook::{LiveOrderbook, OrderbookEvent};
+pub use pool::{ConnectionPool, PoolConfig};
+pub use rate_limit::{LimitScope, RateLimit, RateLimitMode, RateLimiter};
+pub use reconnect::ReconnectConfig;
 pub use rest_client::OKXRestClient;
+pub use retry::RetryConfig;
 pub use websocket_client::OKXWebSocketClient;
@@ -0,0 +1,530 @@
+use crate::client::connection::WsConnection;
+use crate::client::reconnect::ConnectLimiter;
+use crate::client::websocket_client::{OKXWebSocketClient, WebSocketError};
+use crate::models::ws_message::{ChannelArg, WsMessage};
+use futures_util::future::join_all;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::{mpsc, Mutex};
+use tokio::task::JoinHandle;
+
+/// Sizing and pacing of a [`ConnectionPool`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct PoolConfig {
+    /// Number of connections subscriptions are spread across
+    pub connections: usize,
+    /// Channels allowed on a single connection
+    pub max_args_per_connection: usize,
+    /// Minimum time between two connection attempts of the pool, reconnections
+    /// included; OKX allows 3 per second per IP
+    pub connect_interval: Duration,
+    /// How often connections are checked for having stopped
+    ///
+    /// A connection only stops once its reconnection policy gives up, so
+    /// channels are only moved with a bounded
+    /// [`ReconnectConfig::max_attempts`](crate::client::ReconnectConfig::max_attempts).
+    pub health_check_interval: Duration,
+}
+
+impl Default for PoolConfig {
+    fn default() -> Self {
+        PoolConfig {
+            connections: 4,
+            max_args_per_connection: 100,
+            connect_interval: Duration::from_millis(350),
+            health_check_interval: Duration::from_secs(1),
+        }
+    }
+}
+
+/// One pooled connection and the channels it carries
+struct Slot {
+    connection: Arc<WsConnection>,
+    args: Vec<ChannelArg>,
+}
+
+/// Channels sent to one connection in a single request
+type Group = (Arc<WsConnection>, Vec<ChannelArg>);
+
+struct PoolState {
+    client: OKXWebSocketClient,
+    config: PoolConfig,
+    tx: mpsc::Sender<WsMessage>,
+    slots: Vec<Slot>,
+    /// Channels taken from stopped connections that could not be placed yet
+    unassigned: Vec<ChannelArg>,
+    /// Connection that stopped on a fatal error; no connection is opened after it
+    failed: Option<Arc<WsConnection>>,
+}
+
+impl PoolState {
+    fn contains(&self, arg: &ChannelArg) -> bool {
+        self.unassigned.contains(arg) || self.slots.iter().any(|slot| slot.args.contains(arg))
+    }
+
+    /// Picks the connection for one more channel, opening one if needed
+    fn slot_for_new_arg(&mut self) -> Result<usize, WebSocketError> {
+        let max_args = self.config.max_args_per_connection;
+        let least_loaded = (0..self.slots.len())
+            .filter(|&i| {
+                self.slots[i].args.len() < max_args && !self.slots[i].connection.is_closed()
+            })
+            .min_by_key(|&i| self.slots[i].args.len());
+
+        let has_room = self.slots.len() < self.config.connections && self.failed.is_none();
+        match least_loaded {
+            Some(i) if !has_room || self.slots[i].args.is_empty() => Ok(i),
+            _ if has_room => Ok(self.open()),
+            Some(i) => Ok(i),
+            None => Err(self.failure().unwrap_or(WebSocketError::PoolExhausted)),
+        }
+    }
+
+    /// The error that stopped the pool from opening connections, if any
+    fn failure(&self) -> Option<WebSocketError> {
+        let error = match self.failed.as_ref()?.fatal_error()? {
+            WebSocketError::LoginFailed { code, msg } => WebSocketError::LoginFailed {
+                code: code.clone(),
+                msg: msg.clone(),
+            },
+            _ => WebSocketError::ConnectionClosed,
+        };
+        Some(error)
+    }
+
+    /// Opens a new connection; it connects once the shared limiter allows
+    fn open(&mut self) -> usize {
+        let connection = self.client.clone().connect(self.tx.clone());
+        self.slots.push(Slot {
+            connection: Arc::new(connection),
+            args: Vec::new(),
+        });
+        self.slots.len() - 1
+    }
+
+    /// Places `args` on connections, returning the requests to send
+    ///
+    /// Channels are recorded on their connection straight away so that
+    /// concurrent calls do not place them twice. Channels that could not be
+    /// placed are returned with the first error.
+    fn assign(
+        &mut self,
+        args: Vec<ChannelArg>,
+    ) -> (Vec<Group>, Option<(WebSocketError, Vec<ChannelArg>)>) {
+        let mut groups: Vec<Group> = Vec::new();
+        let mut failed = Vec::new();
+        let mut error = None;
+        for arg in args {
+            if self.contains(&arg) {
+                continue;
+            }
+            let index = match self.slot_for_new_arg() {
+                Ok(index) => index,
+                Err(e) => {
+                    error.get_or_insert(e);
+                    failed.push(arg);
+                    continue;
+                }
+            };
+            let slot = &mut self.slots[index];
+            slot.args.push(arg.clone());
+            match groups
+                .iter_mut()
+                .find(|(connection, _)| Arc::ptr_eq(connection, &slot.connection))
+            {
+                Some((_, group)) => group.push(arg),
+                None => groups.push((Arc::clone(&slot.connection), vec![arg])),
+            }
+        }
+        (groups, error.map(|e| (e, failed)))
+    }
+
+    /// Forgets `args` on `connection` after OKX did not accept them
+    fn release(&mut self, connection: &Arc<WsConnection>, args: &[ChannelArg]) {
+        if let Some(slot) = self
+            .slots
+            .iter_mut()
+            .find(|slot| Arc::ptr_eq(&slot.connection, connection))
+        {
+            slot.args.retain(|arg| !args.contains(arg));
+        }
+    }
+
+    /// Removes `args` from the pool, returning the requests to send
+    fn unassign(&mut self, args: Vec<ChannelArg>) -> Vec<Group> {
+        self.unassigned.retain(|arg| !args.contains(arg));
+        let mut groups: Vec<Group> = Vec::new();
+        for arg in args {
+            let Some(slot) = self.slots.iter_mut().find(|slot| slot.args.contains(&arg)) else {
+                continue;
+            };
+            slot.args.retain(|a| *a != arg);
+            match groups
+                .iter_mut()
+                .find(|(connection, _)| Arc::ptr_eq(connection, &slot.connection))
+            {
+                Some((_, group)) => group.push(arg),
+                None => groups.push((Arc::clone(&slot.connection), vec![arg])),
+            }
+        }
+        groups
+    }
+
+    /// Drops stopped connections and takes the channels waiting for a new one
+    ///
+    /// The channels of a connection that stopped on a fatal error, such as a
+    /// rejected login, are dropped since a new connection would fail alike.
+    fn take_orphans(&mut self) -> Vec<ChannelArg> {
+        let (closed, open): (Vec<Slot>, Vec<Slot>) = std::mem::take(&mut self.slots)
+            .into_iter()
+            .partition(|slot| slot.connection.is_closed());
+        self.slots = open;
+        for slot in closed {
+            if slot.connection.fatal_error().is_some() {
+                self.failed.get_or_insert(slot.connection);
+            } else {
+                self.unassigned.extend(slot.args);
+            }
+        }
+        std::mem::take(&mut self.unassigned)
+    }
+}
+
+/// Subscribes to `args`, returning the first error and the channels it affected
+///
+/// The state is only locked to place channels and record the outcome, never
+/// while waiting for OKX.
+async fn subscribe(
+    state: &Mutex<PoolState>,
+    args: Vec<ChannelArg>,
+) -> Result<(), (WebSocketError, Vec<ChannelArg>)> {
+    let (groups, mut error) = state.lock().await.assign(args);
+
+    let requests = groups
+        .iter()
+        .map(|(connection, group)| connection.subscribe(group.clone()));
+    let results = join_all(requests).await;
+
+    let mut state = state.lock().await;
+    for ((connection, group), result) in groups.into_iter().zip(results) {
+        if let Err(e) = result {
+            state.release(&connection, &group);
+            match &mut error {
+                Some((_, failed)) => failed.extend(group),
+                None => error = Some((e, group)),
+            }
+        }
+    }
+    error.map_or(Ok(()), Err)
+}
+
+async fn unsubscribe(
+    state: &Mutex<PoolState>,
+    args: Vec<ChannelArg>,
+) -> Result<(), WebSocketError> {
+    let groups = state.lock().await.unassign(args);
+    let requests = groups
+        .iter()
+        .map(|(connection, group)| connection.unsubscribe(group.clone()));
+    join_all(requests).await.into_iter().collect()
+}
+
+/// Moves the channels of stopped connections onto live ones
+async fn rebalance(state: &Mutex<PoolState>) {
+    let args = state.lock().await.take_orphans();
+    if args.is_empty() {
+        return;
+    }
+    if let Err((_, failed)) = subscribe(state, args).await {
+        state.lock().await.unassigned.extend(failed);
+    }
+}
+
+/// Spreads subscriptions over several WebSocket connections
+///
+/// OKX limits the channels and bandwidth of a single connection as well as
+/// how fast new connections may be opened. The pool places each channel on
+/// the least loaded of up to [`PoolConfig::connections`] connections, lets
+/// them connect and reconnect no faster than one per
+/// [`PoolConfig::connect_interval`], and forwards the
+/// messages of all of them to one `tx`. Every connection reconnects on its
+/// own according to the client's policy; once one gives up, its channels
+/// are moved to the remaining or a freshly opened connection. With the
+/// default policy, which never gives up, channels stay on their connection
+/// and are replayed when it comes back; set
+/// [`ReconnectConfig::max_attempts`](crate::client::ReconnectConfig::max_attempts)
+/// on the client to have them moved instead.
+///
+/// A connection that stops on a fatal error, such as a rejected login, is
+/// not replaced: its channels are dropped and no further connection is
+/// opened, so subscribing beyond the remaining connections fails with that
+/// error.
+pub struct ConnectionPool {
+    state: Arc<Mutex<PoolState>>,
+    monitor: JoinHandle<()>,
+}
+
+impl ConnectionPool {
+    /// Creates an empty pool whose connections are configured like `client`
+    pub fn new(
+        client: OKXWebSocketClient,
+        config: PoolConfig,
+        tx: mpsc::Sender<WsMessage>,
+    ) -> Self {
+        let interval = config.health_check_interval;
+        let limiter = Arc::new(ConnectLimiter::new(config.connect_interval));
+        let state = Arc::new(Mutex::new(PoolState {
+            client: client.with_connect_limiter(limiter),
+            config,
+            tx,
+            slots: Vec::new(),
+            unassigned: Vec::new(),
+            failed: None,
+        }));
+        let monitor = {
+            let state = Arc::clone(&state);
+            tokio::spawn(async move {
+                loop {
+                    tokio::time::sleep(interval).await;
+                    rebalance(&state).await;
+                }
+            })
+        };
+        ConnectionPool { state, monitor }
+    }
+
+    /// Subscribes to `args`, spreading them over the pool's connections
+    ///
+    /// Channels already subscribed are skipped. Channels that could not be
+    /// placed or were rejected are not kept.
+    pub async fn subscribe(&self, args: Vec<ChannelArg>) -> Result<(), WebSocketError> {
+        subscribe(&self.state, args).await.map_err(|(e, _)| e)
+    }
+
+    /// Unsubscribes from `args` on whichever connections carry them
+    pub async fn unsubscribe(&self, args: Vec<ChannelArg>) -> Result<(), WebSocketError> {
+        unsubscribe(&self.state, args).await
+    }
+
+    /// Number of open connections
+    pub async fn connection_count(&self) -> usize {
+        self.state.lock().await.slots.len()
+    }
+
+    /// Number of channels subscribed on each connection
+    pub async fn loads(&self) -> Vec<usize> {
+        let state = self.state.lock().await;
+        state.slots.iter().map(|slot| slot.args.len()).collect()
+    }
+}
+
+impl Drop for ConnectionPool {
+    fn drop(&mut self) {
+        self.monitor.abort();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::testing::{accept, ws_server};
+    use crate::client::ReconnectConfig;
+    use futures_util::{SinkExt, StreamExt};
+    use tokio_tungstenite::accept_async;
+    use tokio_tungstenite::tungstenite::protocol::Message;
+
+    /// Serves connections that acknowledge every argument of every request
+    ///
+    /// The first `drop_first` connections are closed after their first
+    /// request, and connections numbered in `refused` are dropped before the
+    /// handshake. Each request's arguments are reported on the returned channel.
+    async fn ack_server(
+        drop_first: usize,
+        refused: std::ops::Range<usize>,
+    ) -> (String, mpsc::UnboundedReceiver<Vec<ChannelArg>>) {
+        let (listener, url) = ws_server().await;
+        let (requests, received) = mpsc::unbounded_channel();
+        tokio::spawn(async move {
+            for connection in 0.. {
+                let (stream, _) = listener.accept().await.unwrap();
+                if refused.contains(&connection) {
+                    continue;
+                }
+                let requests = requests.clone();
+                tokio::spawn(async move {
+                    let mut ws = accept_async(stream).await.unwrap();
+                    while let Some(Ok(Message::Text(text))) = ws.next().await {
+                        let request: serde_json::Value = serde_json::from_str(&text).unwrap();
+                        let args: Vec<ChannelArg> =
+                            serde_json::from_value(request["args"].clone()).unwrap();
+                        for arg in &args {
+                            let ack = serde_json::json!({
+                                "id": request["id"],
+                                "event": request["op"],
+                                "arg": arg,
+                            });
+                            ws.send(Message::Text(ack.to_string())).await.unwrap();
+                        }
+                        requests.send(args).unwrap();
+                        if connection < drop_first {
+                            ws.close(None).await.unwrap();
+                            break;
+                        }
+                    }
+                });
+            }
+        });
+        (url, received)
+    }
+
+    fn args(inst_ids: &[&str]) -> Vec<ChannelArg> {
+        inst_ids
+            .iter()
+            .map(|inst_id| ChannelArg::instrument("books", inst_id))
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_pool_spreads_subscriptions_and_reports_exhaustion() {
+        let (url, _requests) = ack_server(0, 0..0).await;
+        let (tx, mut rx) = mpsc::channel(64);
+        let config = PoolConfig {
+            connections: 2,
+            max_args_per_connection: 2,
+            connect_interval: Duration::from_millis(10),
+            ..Default::default()
+        };
+        let pool = ConnectionPool::new(OKXWebSocketClient::new(&url), config, tx);
+
+        pool.subscribe(args(&["BTC-USDT", "ETH-USDT", "SOL-USDT"]))
+            .await
+            .unwrap();
+        assert_eq!(pool.loads().await, vec![2, 1]);
+
+        let result = pool
+            .subscribe(args(&["BTC-USDT", "XRP-USDT", "DOGE-USDT"]))
+            .await;
+        assert!(matches!(result, Err(WebSocketError::PoolExhausted)));
+        assert_eq!(pool.loads().await, vec![2, 2]);
+
+        pool.unsubscribe(args(&["ETH-USDT"])).await.unwrap();
+        assert_eq!(pool.loads().await.iter().sum::<usize>(), 3);
+
+        for _ in 0..5 {
+            assert!(matches!(rx.recv().await.unwrap(), WsMessage::Event(_)));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_pool_stays_usable_while_waiting_for_acknowledgements() {
+        let (listener, url) = ws_server().await;
+        tokio::spawn(async move {
+            let mut ws = accept(&listener).await;
+            while let Some(Ok(_)) = ws.next().await {}
+        });
+
+        let (tx, _rx) = mpsc::channel(64);
+        let client = OKXWebSocketClient::new(&url).with_request_timeout(Duration::from_millis(200));
+        let pool = Arc::new(ConnectionPool::new(client, PoolConfig::default(), tx));
+
+        let subscription = {
+            let pool = Arc::clone(&pool);
+            tokio::spawn(async move { pool.subscribe(args(&["BTC-USDT"])).await })
+        };
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        let loads = tokio::time::timeout(Duration::from_millis(50), pool.loads()).await;
+        assert_eq!(loads.unwrap(), vec![1]);
+
+        assert!(matches!(
+            subscription.await.unwrap(),
+            Err(WebSocketError::RequestTimeout(_))
+        ));
+        assert_eq!(pool.loads().await, vec![0]);
+    }
+
+    #[tokio::test]
+    async fn test_pool_moves_channels_off_stopped_connections() {
+        let (url, mut requests) = ack_server(1, 0..0).await;
+        let (tx, _rx) = mpsc::channel(64);
+        let client = OKXWebSocketClient::new(&url).with_reconnect(ReconnectConfig::disabled());
+        let config = PoolConfig {
+            connections: 1,
+            connect_interval: Duration::from_millis(10),
+            health_check_interval: Duration::from_millis(20),
+            ..Default::default()
+        };
+        let pool = ConnectionPool::new(client, config, tx);
+
+        pool.subscribe(args(&["BTC-USDT", "ETH-USDT"]))
+            .await
+            .unwrap();
+        assert_eq!(requests.recv().await.unwrap().len(), 2);
+        assert_eq!(
+            requests.recv().await.unwrap(),
+            args(&["BTC-USDT", "ETH-USDT"])
+        );
+        assert_eq!(pool.connection_count().await, 1);
+    }
+
+    #[tokio::test]
+    async fn test_pool_does_not_reopen_after_login_failure() {
+        let (listener, url) = ws_server().await;
+        let (accepted, mut connections) = mpsc::unbounded_channel();
+        tokio::spawn(async move {
+            loop {
+                let mut ws = accept(&listener).await;
+                accepted.send(()).unwrap();
+                ws.next().await.unwrap().unwrap();
+                let error =
+                    r#"{"event":"error","code":"60009","msg":"Login failed.","connId":"a4d3ae55"}"#;
+                ws.send(Message::Text(error.into())).await.unwrap();
+            }
+        });
+
+        let (tx, _rx) = mpsc::channel(64);
+        let client = OKXWebSocketClient::new(&url).with_credentials(
+            crate::client::Credentials::new("key", "secret", "passphrase"),
+        );
+        let config = PoolConfig {
+            connections: 1,
+            connect_interval: Duration::from_millis(10),
+            health_check_interval: Duration::from_millis(20),
+            ..Default::default()
+        };
+        let pool = ConnectionPool::new(client, config, tx);
+
+        assert!(pool.subscribe(args(&["BTC-USDT"])).await.is_err());
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        assert_eq!(pool.connection_count().await, 0);
+        assert!(matches!(
+            pool.subscribe(args(&["ETH-USDT"])).await,
+            Err(WebSocketError::LoginFailed { code, .. }) if code == "60009"
+        ));
+        connections.recv().await.unwrap();
+        assert!(connections.try_recv().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_pool_moves_channels_once_bounded_reconnects_give_up() {
+        let (url, mut requests) = ack_server(1, 1..3).await;
+        let (tx, _rx) = mpsc::channel(64);
+        let client = OKXWebSocketClient::new(&url).with_reconnect(ReconnectConfig {
+            initial_delay: Duration::from_millis(10),
+            max_attempts: Some(2),
+            ..Default::default()
+        });
+        let config = PoolConfig {
+            connections: 1,
+            connect_interval: Duration::from_millis(10),
+            health_check_interval: Duration::from_millis(20),
+            ..Default::default()
+        };
+        let pool = ConnectionPool::new(client, config, tx);
+
+        pool.subscribe(args(&["BTC-USDT"])).await.unwrap();
+        assert_eq!(requests.recv().await.unwrap(), args(&["BTC-USDT"]));
+        // Both reconnection attempts are refused, so the channel moves to a new connection
+        assert_eq!(requests.recv().await.unwrap(), args(&["BTC-USDT"]));
+        assert_eq!(pool.loads().await, vec![1]);
+    }
+}
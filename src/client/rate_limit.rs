@@ -0,0 +1,326 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::Duration;
+use tokio::time::{sleep, Instant};
+
+/// Which requests of an endpoint share a limit
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LimitScope {
+    /// All requests to the endpoint share one limit (per IP or user ID)
+    Endpoint,
+    /// Each instrument has its own limit (per user ID and instrument ID)
+    Instrument,
+}
+
+/// Requests allowed per time window on one endpoint
+///
+/// A limit of zero requests or with a zero window allows no requests at
+/// all: they fail at once with `OKXClientError::RateLimited`, even in
+/// [`RateLimitMode::Queue`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RateLimit {
+    pub requests: u32,
+    pub per: Duration,
+    pub scope: LimitScope,
+}
+
+impl RateLimit {
+    /// Limit shared by all requests to an endpoint
+    pub fn new(requests: u32, per: Duration) -> Self {
+        RateLimit {
+            requests,
+            per,
+            scope: LimitScope::Endpoint,
+        }
+    }
+
+    /// Limit applied separately to each instrument
+    pub fn per_instrument(requests: u32, per: Duration) -> Self {
+        RateLimit {
+            scope: LimitScope::Instrument,
+            ..RateLimit::new(requests, per)
+        }
+    }
+
+    /// Returns `false` if no request can ever pass this limit
+    fn allows_requests(&self) -> bool {
+        self.requests > 0 && !self.per.is_zero()
+    }
+
+    /// Tokens regained per second
+    fn rate(&self) -> f64 {
+        f64::from(self.requests) / self.per.as_secs_f64()
+    }
+}
+
+/// What to do with a request once its limit is used up
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum RateLimitMode {
+    /// Wait until the request fits within the limit
+    #[default]
+    Queue,
+    /// Fail at once with `OKXClientError::RateLimited`
+    Reject,
+}
+
+/// Limits documented by OKX for the endpoints this crate supports
+fn okx_limits() -> HashMap<String, RateLimit> {
+    let two_seconds = Duration::from_secs(2);
+    [
+        ("/api/v5/market/books", RateLimit::new(40, two_seconds)),
+        ("/api/v5/market/books-full", RateLimit::new(10, two_seconds)),
+        (
+            "/api/v5/market/books-lite",
+            RateLimit::new(6, Duration::from_secs(1)),
+        ),
+        ("/api/v5/market/ticker", RateLimit::new(20, two_seconds)),
+        ("/api/v5/market/candles", RateLimit::new(40, two_seconds)),
+        ("/api/v5/market/trades", RateLimit::new(100, two_seconds)),
+        (
+            "/api/v5/market/history-trades",
+            RateLimit::new(20, two_seconds),
+        ),
+        (
+            "/api/v5/market/history-candles",
+            RateLimit::new(20, two_seconds),
+        ),
+        ("/api/v5/market/tickers", RateLimit::new(20, two_seconds)),
+        (
+            "/api/v5/trade/order",
+            RateLimit::per_instrument(60, two_seconds),
+        ),
+        // Batch limits count orders, each charged to its own instrument
+        (
+            "/api/v5/trade/batch-orders",
+            RateLimit::per_instrument(300, two_seconds),
+        ),
+        (
+            "/api/v5/trade/cancel-order",
+            RateLimit::per_instrument(60, two_seconds),
+        ),
+        (
+            "/api/v5/trade/cancel-batch-orders",
+            RateLimit::per_instrument(300, two_seconds),
+        ),
+        (
+            "/api/v5/trade/amend-order",
+            RateLimit::per_instrument(60, two_seconds),
+        ),
+        (
+            "/api/v5/trade/amend-batch-orders",
+            RateLimit::per_instrument(300, two_seconds),
+        ),
+        ("/api/v5/trade/mass-cancel", RateLimit::new(5, two_seconds)),
+        ("/api/v5/account/balance", RateLimit::new(10, two_seconds)),
+        ("/api/v5/account/positions", RateLimit::new(10, two_seconds)),
+    ]
+    .into_iter()
+    .map(|(endpoint, limit)| (endpoint.to_string(), limit))
+    .collect()
+}
+
+#[derive(Debug)]
+struct Bucket {
+    /// Tokens available; negative while requests are queued for future tokens
+    tokens: f64,
+    updated: Instant,
+}
+
+/// Client-side token buckets mirroring OKX's REST rate limits
+///
+/// Each endpoint with a known limit gets a bucket holding `requests` tokens,
+/// refilled continuously over `per`; endpoints limited per instrument get one
+/// bucket per `instId`. Endpoints without a limit are never delayed.
+#[derive(Debug)]
+pub struct RateLimiter {
+    limits: HashMap<String, RateLimit>,
+    mode: RateLimitMode,
+    buckets: Mutex<HashMap<(String, Option<String>), Bucket>>,
+}
+
+impl Default for RateLimiter {
+    fn default() -> Self {
+        RateLimiter::with_limits(okx_limits())
+    }
+}
+
+impl RateLimiter {
+    /// Limiter without any limit, never delaying a request
+    pub fn disabled() -> Self {
+        RateLimiter::with_limits(HashMap::new())
+    }
+
+    fn with_limits(limits: HashMap<String, RateLimit>) -> Self {
+        RateLimiter {
+            limits,
+            mode: RateLimitMode::default(),
+            buckets: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn with_mode(mut self, mode: RateLimitMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    /// Sets the limit of `endpoint` (e.g. `/api/v5/market/books`), replacing any default
+    pub fn with_limit(mut self, endpoint: &str, limit: RateLimit) -> Self {
+        self.limits.insert(endpoint.to_string(), limit);
+        self
+    }
+
+    pub fn limit(&self, endpoint: &str) -> Option<&RateLimit> {
+        self.limits.get(endpoint)
+    }
+
+    pub fn mode(&self) -> RateLimitMode {
+        self.mode
+    }
+
+    /// Takes a token for a request to `endpoint`, waiting for one in queue mode
+    ///
+    /// Returns how long the caller would have to wait when the limit is used
+    /// up in reject mode.
+    pub async fn acquire(&self, endpoint: &str, inst_id: Option<&str>) -> Result<(), Duration> {
+        self.acquire_weighted(endpoint, &[(inst_id, 1)]).await
+    }
+
+    /// Takes `weight` tokens per `(instId, weight)` pair for one request to `endpoint`
+    ///
+    /// Used for batch requests, whose limit counts every order against its
+    /// own instrument. On endpoints limited as a whole the weights add up.
+    /// Either all tokens are taken or, in reject mode, none are.
+    pub async fn acquire_weighted(
+        &self,
+        endpoint: &str,
+        weights: &[(Option<&str>, u32)],
+    ) -> Result<(), Duration> {
+        let Some(limit) = self.limits.get(endpoint) else {
+            return Ok(());
+        };
+        if !limit.allows_requests() {
+            return Err(limit.per);
+        }
+        let mut charges: Vec<((String, Option<String>), f64)> = Vec::new();
+        for &(inst_id, weight) in weights {
+            let key = match limit.scope {
+                LimitScope::Endpoint => (endpoint.to_string(), None),
+                LimitScope::Instrument => (endpoint.to_string(), inst_id.map(str::to_string)),
+            };
+            match charges.iter_mut().find(|(k, _)| *k == key) {
+                Some((_, total)) => *total += f64::from(weight),
+                None => charges.push((key, f64::from(weight))),
+            }
+        }
+        let capacity = f64::from(limit.requests);
+        let rate = limit.rate();
+
+        let wait = {
+            let mut buckets = self.buckets.lock().unwrap();
+            let now = Instant::now();
+            for (key, _) in &charges {
+                let bucket = buckets.entry(key.clone()).or_insert(Bucket {
+                    tokens: capacity,
+                    updated: now,
+                });
+                let elapsed = now.duration_since(bucket.updated).as_secs_f64();
+                bucket.tokens = (bucket.tokens + elapsed * rate).min(capacity);
+                bucket.updated = now;
+            }
+
+            if self.mode == RateLimitMode::Reject {
+                // A charge above the capacity passes once the bucket is full
+                let shortfall = charges
+                    .iter()
+                    .map(|(key, weight)| weight.min(capacity) - buckets[key].tokens)
+                    .fold(0.0, f64::max);
+                if shortfall > 0.0 {
+                    return Err(Duration::from_secs_f64(shortfall / rate));
+                }
+            }
+            // Queued requests reserve future tokens, so they leave in order
+            let mut debt: f64 = 0.0;
+            for (key, weight) in &charges {
+                if let Some(bucket) = buckets.get_mut(key) {
+                    bucket.tokens -= weight;
+                    debt = debt.max(-bucket.tokens);
+                }
+            }
+            (debt > 0.0).then(|| Duration::from_secs_f64(debt / rate))
+        };
+
+        if let Some(wait) = wait {
+            sleep(wait).await;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_buckets_per_endpoint_and_instrument() {
+        let limiter = RateLimiter::default()
+            .with_mode(RateLimitMode::Reject)
+            .with_limit(
+                "/api/v5/trade/order",
+                RateLimit::per_instrument(2, Duration::from_secs(60)),
+            );
+        assert_eq!(limiter.limit("/api/v5/market/books").unwrap().requests, 40);
+
+        let order = "/api/v5/trade/order";
+        assert!(limiter.acquire(order, Some("BTC-USDT")).await.is_ok());
+        assert!(limiter.acquire(order, Some("BTC-USDT")).await.is_ok());
+        let retry_after = limiter.acquire(order, Some("BTC-USDT")).await.unwrap_err();
+        assert!(retry_after > Duration::from_secs(29));
+        assert!(limiter.acquire(order, Some("ETH-USDT")).await.is_ok());
+        for _ in 0..100 {
+            assert!(limiter.acquire("/api/v5/unlimited", None).await.is_ok());
+        }
+    }
+
+    #[tokio::test]
+    async fn test_empty_limits_allow_no_requests() {
+        let limiter = RateLimiter::disabled()
+            .with_limit("/none", RateLimit::new(0, Duration::from_secs(1)))
+            .with_limit("/instant", RateLimit::per_instrument(10, Duration::ZERO));
+        assert_eq!(
+            limiter.acquire("/none", None).await,
+            Err(Duration::from_secs(1))
+        );
+        assert!(limiter.acquire("/instant", Some("BTC-USDT")).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_batches_charge_each_order_to_its_instrument() {
+        let limiter = RateLimiter::default().with_mode(RateLimitMode::Reject);
+        let batch = "/api/v5/trade/batch-orders";
+
+        for _ in 0..15 {
+            let orders = [(Some("BTC-USDT"), 20)];
+            assert!(limiter.acquire_weighted(batch, &orders).await.is_ok());
+        }
+        let orders = [(Some("BTC-USDT"), 1), (Some("ETH-USDT"), 19)];
+        assert!(limiter.acquire_weighted(batch, &orders).await.is_err());
+        // A rejected batch takes no tokens, even from instruments with room
+        let orders = [(Some("ETH-USDT"), 300)];
+        assert!(limiter.acquire_weighted(batch, &orders).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_queue_mode_spaces_out_requests() {
+        let limiter = RateLimiter::disabled()
+            .with_limit("/books", RateLimit::new(2, Duration::from_millis(200)));
+
+        let start = Instant::now();
+        for _ in 0..4 {
+            limiter.acquire("/books", None).await.unwrap();
+        }
+        // Two requests pass at once, the next two wait 100ms each
+        let elapsed = start.elapsed();
+        assert!(elapsed >= Duration::from_millis(190), "{:?}", elapsed);
+        assert!(elapsed < Duration::from_millis(400), "{:?}", elapsed);
+    }
+}
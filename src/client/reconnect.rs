@@ -0,0 +1,147 @@
+use std::sync::Mutex;
+use std::time::Duration;
+use tokio::time::Instant;
+
+/// Exponential backoff policy used when a WebSocket connection drops
+///
+/// The delay before reconnection attempt `n` (starting at 1) is
+/// `initial_delay * multiplier^(n - 1)`, capped at `max_delay`, and then
+/// spread by up to `jitter` (a fraction of the delay) in either direction so
+/// that many clients do not reconnect in lockstep.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReconnectConfig {
+    /// Delay before the first reconnection attempt
+    pub initial_delay: Duration,
+    /// Upper bound for the delay before jitter is applied
+    pub max_delay: Duration,
+    /// Factor applied to the delay after each failed attempt
+    pub multiplier: f64,
+    /// Random spread as a fraction of the delay, between 0.0 and 1.0
+    pub jitter: f64,
+    /// Consecutive failed attempts allowed before giving up, `None` for unlimited
+    pub max_attempts: Option<u32>,
+}
+
+impl Default for ReconnectConfig {
+    fn default() -> Self {
+        ReconnectConfig {
+            initial_delay: Duration::from_millis(500),
+            max_delay: Duration::from_secs(30),
+            multiplier: 2.0,
+            jitter: 0.2,
+            max_attempts: None,
+        }
+    }
+}
+
+impl ReconnectConfig {
+    /// Policy that never reconnects
+    pub fn disabled() -> Self {
+        ReconnectConfig {
+            max_attempts: Some(0),
+            ..Default::default()
+        }
+    }
+
+    /// Returns `true` if reconnection attempt `attempt` (starting at 1) is allowed
+    pub fn should_retry(&self, attempt: u32) -> bool {
+        self.max_attempts.is_none_or(|max| attempt <= max)
+    }
+
+    /// Returns the delay to wait before reconnection attempt `attempt`
+    pub fn delay(&self, attempt: u32) -> Duration {
+        let exponent = attempt.saturating_sub(1).min(i32::MAX as u32) as i32;
+        let base = self.initial_delay.as_secs_f64() * self.multiplier.powi(exponent);
+        let base = base.min(self.max_delay.as_secs_f64());
+        let jitter = self.jitter.clamp(0.0, 1.0);
+        let spread = 1.0 + jitter * (fastrand::f64() * 2.0 - 1.0);
+        Duration::from_secs_f64((base * spread).max(0.0))
+    }
+}
+
+/// Spaces out connection attempts shared by several clients
+///
+/// OKX limits how many connections an IP may open per second. Every
+/// attempt, including reconnections, takes the next free slot at least
+/// `interval` after the previous one.
+#[derive(Debug)]
+pub(crate) struct ConnectLimiter {
+    interval: Duration,
+    next: Mutex<Option<Instant>>,
+}
+
+impl ConnectLimiter {
+    pub(crate) fn new(interval: Duration) -> Self {
+        ConnectLimiter {
+            interval,
+            next: Mutex::new(None),
+        }
+    }
+
+    /// Waits for this attempt's slot
+    pub(crate) async fn wait(&self) {
+        let slot = {
+            let mut next = self.next.lock().unwrap_or_else(|e| e.into_inner());
+            let now = Instant::now();
+            let slot = next.map_or(now, |next| next.max(now));
+            *next = Some(slot + self.interval);
+            slot
+        };
+        tokio::time::sleep_until(slot).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    #[tokio::test]
+    async fn test_connect_limiter_spaces_out_attempts() {
+        let limiter = Arc::new(ConnectLimiter::new(Duration::from_millis(50)));
+        let start = Instant::now();
+        let attempts: Vec<_> = (0..3)
+            .map(|_| {
+                let limiter = Arc::clone(&limiter);
+                tokio::spawn(async move {
+                    limiter.wait().await;
+                    start.elapsed()
+                })
+            })
+            .collect();
+        let mut elapsed = Vec::new();
+        for attempt in attempts {
+            elapsed.push(attempt.await.unwrap());
+        }
+        elapsed.sort();
+        assert!(elapsed[0] < Duration::from_millis(50));
+        assert!(elapsed[1] >= Duration::from_millis(50));
+        assert!(elapsed[2] >= Duration::from_millis(100));
+    }
+
+    #[test]
+    fn test_delay_grows_exponentially_and_is_capped() {
+        let config = ReconnectConfig {
+            initial_delay: Duration::from_secs(1),
+            max_delay: Duration::from_secs(10),
+            multiplier: 2.0,
+            jitter: 0.0,
+            max_attempts: Some(5),
+        };
+        assert_eq!(config.delay(1), Duration::from_secs(1));
+        assert_eq!(config.delay(3), Duration::from_secs(4));
+        assert_eq!(config.delay(8), Duration::from_secs(10));
+        assert!(config.should_retry(5));
+        assert!(!config.should_retry(6));
+        assert!(!ReconnectConfig::disabled().should_retry(1));
+
+        let jittered = ReconnectConfig {
+            jitter: 0.5,
+            ..config
+        };
+        for _ in 0..100 {
+            let delay = jittered.delay(2);
+            assert!(delay >= Duration::from_secs(1) && delay <= Duration::from_secs(3));
+        }
+    }
+}
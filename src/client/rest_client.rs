@@ -1,9 +1,21 @@
-use crate::models::Orderbook;
-use reqwest::Client;
+use crate::client::auth::{iso_timestamp, Credentials};
+use crate::client::environment::Environment;
+use crate::client::rate_limit::RateLimiter;
+use crate::client::retry::RetryConfig;
+use crate::models::orderbook::OrderbookError;
+use crate::models::{
+    ApiErrorKind, ApiResponse, Bar, Candle, Level, Orderbook, Ticker, Trade, TradeCursor,
+};
+use futures_util::{stream, Stream, TryStreamExt};
+use reqwest::header::CONTENT_TYPE;
+use reqwest::{Client, Method, StatusCode};
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use std::num::ParseIntError;
+use std::time::Duration;
 use thiserror::Error;
+use tokio::time::sleep;
 use url::Url;
 
 #[derive(Error, Debug)]
@@ -18,40 +30,148 @@ pub enum OKXClientError {
     ParseFloatError(#[from] std::num::ParseFloatError),
     #[error("Failed to parse integer: {0}")]
     ParseIntError(#[from] ParseIntError),
+    #[error("Invalid order book: {0}")]
+    InvalidOrderbook(#[from] OrderbookError),
     #[error("Unexpected response structure: {0}")]
     UnexpectedResponseStructure(String),
+    #[error("Invalid parameter: {0}")]
+    InvalidParameter(String),
+    #[error("OKX API error {code}: {msg}")]
+    Api { code: String, msg: String },
+    /// All (`code` `"1"`) or some (`code` `"2"`) items of a batch request
+    /// failed; each entry of `data` carries its own `sCode` and `sMsg`
+    #[error("OKX API error {code}: {}", item_errors(.data))]
+    ItemsFailed { code: String, data: Vec<Value> },
+    #[error("Rate limit of {endpoint} reached, retry after {retry_after:?}")]
+    RateLimited {
+        endpoint: String,
+        retry_after: Duration,
+    },
+    #[error("HTTP {status}: {body}")]
+    HttpStatus { status: u16, body: String },
+    #[error("Request failed after {attempts} attempts: {source}")]
+    RetriesExhausted {
+        attempts: u32,
+        #[source]
+        source: Box<OKXClientError>,
+    },
+}
+
+/// Lists the `sCode: sMsg` of every failed item in a batch response
+fn item_errors(data: &[Value]) -> String {
+    data.iter()
+        .filter(|item| item["sCode"].as_str().is_some_and(|code| code != "0"))
+        .map(|item| {
+            let field = |name: &str| item[name].as_str().unwrap_or_default().to_string();
+            format!("{}: {}", field("sCode"), field("sMsg"))
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+impl OKXClientError {
+    /// Category of an OKX API error, or `None` for any other failure
+    ///
+    /// Requests rejected by the client-side rate limiter count as `RateLimited`.
+    pub fn api_error_kind(&self) -> Option<ApiErrorKind> {
+        match self {
+            OKXClientError::Api { code, .. } | OKXClientError::ItemsFailed { code, .. } => {
+                Some(ApiErrorKind::from_code(code))
+            }
+            OKXClientError::RateLimited { .. } => Some(ApiErrorKind::RateLimited),
+            OKXClientError::RetriesExhausted { source, .. } => source.api_error_kind(),
+            _ => None,
+        }
+    }
+
+    /// Returns `true` if sending the same request again may succeed
+    ///
+    /// Covers transport errors, HTTP 429 and 5xx responses, and OKX codes
+    /// classified as rate limited or system busy.
+    pub fn is_transient(&self) -> bool {
+        match self {
+            OKXClientError::RequestFailed(error) => !error.is_builder(),
+            OKXClientError::HttpStatus { status, .. } => {
+                *status == StatusCode::TOO_MANY_REQUESTS.as_u16() || *status >= 500
+            }
+            OKXClientError::Api { code, .. } => ApiErrorKind::from_code(code).is_retryable(),
+            _ => false,
+        }
+    }
 }
 
 /// Internal representation of raw order book data from the API
 #[derive(Debug, Serialize, Deserialize)]
 struct RawOrderbook {
-    asks: Vec<(String, String, String, String)>,
-    bids: Vec<(String, String, String, String)>,
+    asks: Vec<Level>,
+    bids: Vec<Level>,
     ts: String,
 }
 
+/// Deepest `sz` accepted by `/api/v5/market/books`
+pub const MAX_BOOK_DEPTH: u32 = 400;
+
+/// Deepest `sz` accepted by `/api/v5/market/books-full`
+pub const MAX_FULL_BOOK_DEPTH: u32 = 5000;
+
+/// Most candles returned by one `/api/v5/market/candles` request
+pub const CANDLES_PAGE_SIZE: u32 = 300;
+
+/// Most candles returned by one `/api/v5/market/history-candles` request
+pub const HISTORY_CANDLES_PAGE_SIZE: u32 = 100;
+
+/// Most trades returned by one `/api/v5/market/trades` request
+pub const MAX_TRADES: u32 = 500;
+
+/// Most trades returned by one `/api/v5/market/history-trades` request
+pub const HISTORY_TRADES_PAGE_SIZE: u32 = 100;
+
+/// Position of a paginated trade download
+struct TradeCursorState {
+    symbol: String,
+    start: u64,
+    after: TradeCursor,
+    done: bool,
+}
+
+/// Position of a paginated candle download
+struct CandleCursor {
+    symbol: String,
+    bar: Bar,
+    start: u64,
+    /// Only candles opened before this time are still to be fetched
+    after: u64,
+    /// `candles` only covers recent bars; older ones come from `history-candles`
+    history: bool,
+    done: bool,
+}
+
 impl RawOrderbook {
-    fn parse_to_orderbook(&self) -> Result<Orderbook, OKXClientError> {
-        Ok(Orderbook {
-            asks: self.parse_vec(&self.asks)?,
-            bids: self.parse_vec(&self.bids)?,
-            ts: self.ts.parse::<u64>()?,
-        })
+    /// Reads `books-full` levels, sent as `[price, size, numOrders]`
+    fn into_full_book(mut self) -> Self {
+        for level in self.asks.iter_mut().chain(self.bids.iter_mut()) {
+            level.num_orders = level.liquidated_orders;
+            level.liquidated_orders = 0;
+        }
+        self
     }
 
-    fn parse_vec(
-        &self,
-        vec: &[(String, String, String, String)],
-    ) -> Result<Vec<(f64, f64)>, OKXClientError> {
-        vec.iter()
-            .map(|(price, amount, _, _)| Ok((price.parse::<f64>()?, amount.parse::<f64>()?)))
-            .collect()
+    fn parse_to_orderbook(self) -> Result<Orderbook, OKXClientError> {
+        Ok(Orderbook::new(
+            self.asks,
+            self.bids,
+            self.ts.parse::<u64>()?,
+        )?)
     }
 }
 
 pub struct OKXRestClient {
     base_url: Url,
     client: Client,
+    credentials: Option<Credentials>,
+    environment: Environment,
+    rate_limiter: RateLimiter,
+    retry: RetryConfig,
 }
 
 impl OKXRestClient {
@@ -62,34 +182,499 @@ impl OKXRestClient {
                 .timeout(std::time::Duration::from_secs(30))
                 .user_agent("OKX-Rust-Client/1.0")
                 .build()?,
+            credentials: None,
+            environment: Environment::Production,
+            rate_limiter: RateLimiter::default(),
+            retry: RetryConfig::default(),
         })
     }
 
-    pub async fn get_order_book(&self, symbol: &str) -> Result<Orderbook, OKXClientError> {
-        let url = self
-            .base_url
-            .join(&format!("api/v5/market/books?instId={}", symbol))?;
-        let response_text = self.client.get(url).send().await?.text().await?;
-
-        let response_value: Value = serde_json::from_str(&response_text)?;
-
-        // Check if the response has the expected structure
-        let orderbook_data = response_value["data"]
-            .as_array()
-            .and_then(|arr| arr.first())
-            .ok_or_else(|| {
-                OKXClientError::UnexpectedResponseStructure("Missing 'data' array or empty".into())
+    /// Sends every request to `environment`; demo trading adds `x-simulated-trading: 1`
+    pub fn with_environment(mut self, environment: Environment) -> Self {
+        self.environment = environment;
+        self
+    }
+
+    /// Signs every request with `credentials`, as required by private endpoints
+    pub fn with_credentials(mut self, credentials: Credentials) -> Self {
+        self.credentials = Some(credentials);
+        self
+    }
+
+    /// Replaces the default rate limiter, which queues requests within OKX's limits
+    pub fn with_rate_limiter(mut self, rate_limiter: RateLimiter) -> Self {
+        self.rate_limiter = rate_limiter;
+        self
+    }
+
+    /// Sets the retry policy for transient failures
+    pub fn with_retry(mut self, retry: RetryConfig) -> Self {
+        self.retry = retry;
+        self
+    }
+
+    /// Sends a GET request to `path` (e.g. `/api/v5/account/balance`) with `query`
+    ///
+    /// Returns the `data` records of the response, or `OKXClientError::Api`
+    /// when OKX answers with a non-zero `code`.
+    pub async fn get<T: DeserializeOwned>(
+        &self,
+        path: &str,
+        query: &[(&str, &str)],
+    ) -> Result<Vec<T>, OKXClientError> {
+        self.execute(Method::GET, path, query, None).await
+    }
+
+    /// Sends a POST request to `path` with `body` encoded as JSON
+    ///
+    /// Not retried unless the retry policy enables POST retries.
+    pub async fn post<B: Serialize + ?Sized, T: DeserializeOwned>(
+        &self,
+        path: &str,
+        body: &B,
+    ) -> Result<Vec<T>, OKXClientError> {
+        let body = serde_json::to_string(body)?;
+        self.execute(Method::POST, path, &[], Some(body)).await
+    }
+
+    /// Sends a request and decodes its response, retrying transient failures
+    ///
+    /// When retries were made, the last error is wrapped in
+    /// `OKXClientError::RetriesExhausted` with the number of attempts.
+    async fn execute<T: DeserializeOwned>(
+        &self,
+        method: Method,
+        path: &str,
+        query: &[(&str, &str)],
+        body: Option<String>,
+    ) -> Result<Vec<T>, OKXClientError> {
+        let idempotent = method == Method::GET;
+        let mut retries = 0;
+        loop {
+            let error = match self
+                .send(method.clone(), path, query, body.clone())
+                .await
+                .and_then(|text| Self::decode(&text))
+            {
+                Ok(data) => return Ok(data),
+                Err(e) => e,
+            };
+
+            let may_retry = (idempotent || self.retry.retry_posts)
+                && error.is_transient()
+                && retries < self.retry.max_retries;
+            if !may_retry {
+                if retries == 0 {
+                    return Err(error);
+                }
+                return Err(OKXClientError::RetriesExhausted {
+                    attempts: retries + 1,
+                    source: Box::new(error),
+                });
+            }
+            retries += 1;
+            sleep(self.retry.delay(retries)).await;
+        }
+    }
+
+    /// Unwraps the `{code, msg, data}` envelope of a response
+    ///
+    /// The `code` is checked before `data` is decoded, since error responses
+    /// carry data of another shape, if any.
+    fn decode<T: DeserializeOwned>(text: &str) -> Result<Vec<T>, OKXClientError> {
+        let response: ApiResponse<Value> = serde_json::from_str(text)?;
+        if !response.is_success() {
+            let has_items = response.data.iter().any(|item| item.get("sCode").is_some());
+            if matches!(response.code.as_str(), "1" | "2") && has_items {
+                return Err(OKXClientError::ItemsFailed {
+                    code: response.code,
+                    data: response.data,
+                });
+            }
+            return Err(OKXClientError::Api {
+                code: response.code,
+                msg: response.msg,
+            });
+        }
+        response
+            .data
+            .into_iter()
+            .map(serde_json::from_value)
+            .collect::<Result<_, _>>()
+            .map_err(Into::into)
+    }
+
+    /// Sends a request, signing it when credentials are set
+    ///
+    /// The request first waits for the rate limiter. The signature covers
+    /// the timestamp, the method, the path including the query string, and
+    /// the body, and is sent with the API key, timestamp and passphrase in
+    /// the `OK-ACCESS-*` headers.
+    async fn send(
+        &self,
+        method: Method,
+        path: &str,
+        query: &[(&str, &str)],
+        body: Option<String>,
+    ) -> Result<String, OKXClientError> {
+        let endpoint = format!("/{}", path.trim_start_matches('/'));
+        let weights = Self::inst_weights(query, body.as_deref());
+        let weights: Vec<_> = weights
+            .iter()
+            .map(|(inst_id, weight)| (inst_id.as_deref(), *weight))
+            .collect();
+        self.rate_limiter
+            .acquire_weighted(&endpoint, &weights)
+            .await
+            .map_err(|retry_after| OKXClientError::RateLimited {
+                endpoint: endpoint.clone(),
+                retry_after,
             })?;
 
-        let raw_orderbook: RawOrderbook = serde_json::from_value(orderbook_data.clone())?;
-        raw_orderbook.parse_to_orderbook()
+        let mut url = self.base_url.join(path.trim_start_matches('/'))?;
+        if !query.is_empty() {
+            url.query_pairs_mut().extend_pairs(query);
+        }
+
+        let mut request = self.client.request(method.clone(), url.clone());
+        if let Some(credentials) = &self.credentials {
+            let request_path = match url.query() {
+                Some(query) => format!("{}?{}", url.path(), query),
+                None => url.path().to_string(),
+            };
+            let timestamp = iso_timestamp();
+            let sign = credentials.sign(
+                &timestamp,
+                method.as_str(),
+                &request_path,
+                body.as_deref().unwrap_or_default(),
+            );
+            request = request
+                .header("OK-ACCESS-KEY", credentials.api_key())
+                .header("OK-ACCESS-SIGN", sign)
+                .header("OK-ACCESS-TIMESTAMP", timestamp)
+                .header("OK-ACCESS-PASSPHRASE", credentials.passphrase());
+        }
+        if self.environment.is_demo() {
+            request = request.header("x-simulated-trading", "1");
+        }
+        if let Some(body) = body {
+            request = request.header(CONTENT_TYPE, "application/json").body(body);
+        }
+
+        let response = request.send().await?;
+        let status = response.status();
+        let text = response.text().await?;
+        // OKX reports most errors in the JSON envelope, even with an error status
+        if !status.is_success() && serde_json::from_str::<ApiResponse<Value>>(&text).is_err() {
+            return Err(OKXClientError::HttpStatus {
+                status: status.as_u16(),
+                body: text,
+            });
+        }
+        Ok(text)
+    }
+
+    /// Instruments a request is about and how many entries each has
+    ///
+    /// Read from the `instId` of the query or JSON body; a batch body counts
+    /// every entry against its own instrument.
+    fn inst_weights(query: &[(&str, &str)], body: Option<&str>) -> Vec<(Option<String>, u32)> {
+        if let Some((_, inst_id)) = query.iter().find(|(key, _)| *key == "instId") {
+            return vec![(Some(inst_id.to_string()), 1)];
+        }
+        let body: Option<Value> = body.and_then(|body| serde_json::from_str(body).ok());
+        let entries = match &body {
+            Some(Value::Array(entries)) if !entries.is_empty() => entries.iter().collect(),
+            Some(entry) => vec![entry],
+            None => return vec![(None, 1)],
+        };
+        let mut weights: Vec<(Option<String>, u32)> = Vec::new();
+        for entry in entries {
+            let inst_id = entry["instId"].as_str().map(str::to_string);
+            match weights.iter_mut().find(|(id, _)| *id == inst_id) {
+                Some((_, weight)) => *weight += 1,
+                None => weights.push((inst_id, 1)),
+            }
+        }
+        weights
+    }
+
+    /// Fetches the order book of `symbol` at OKX's default depth of 1 level
+    pub async fn get_order_book(&self, symbol: &str) -> Result<Orderbook, OKXClientError> {
+        self.fetch_order_book("api/v5/market/books", &[("instId", symbol)])
+            .await?
+            .parse_to_orderbook()
+    }
+
+    /// Fetches up to `depth` levels per side, at most `MAX_BOOK_DEPTH`
+    pub async fn get_order_book_with_depth(
+        &self,
+        symbol: &str,
+        depth: u32,
+    ) -> Result<Orderbook, OKXClientError> {
+        let sz = Self::bounded_param("depth", depth, MAX_BOOK_DEPTH)?;
+        self.fetch_order_book("api/v5/market/books", &[("instId", symbol), ("sz", &sz)])
+            .await?
+            .parse_to_orderbook()
+    }
+
+    /// Fetches up to `depth` levels per side from `books-full`, at most `MAX_FULL_BOOK_DEPTH`
+    ///
+    /// The full book carries no liquidated order counts, so
+    /// `Level::liquidated_orders` is always zero.
+    pub async fn get_full_order_book(
+        &self,
+        symbol: &str,
+        depth: u32,
+    ) -> Result<Orderbook, OKXClientError> {
+        let sz = Self::bounded_param("depth", depth, MAX_FULL_BOOK_DEPTH)?;
+        self.fetch_order_book(
+            "api/v5/market/books-full",
+            &[("instId", symbol), ("sz", &sz)],
+        )
+        .await?
+        .into_full_book()
+        .parse_to_orderbook()
+    }
+
+    /// Fetches the 25-level book from `books-lite`, which is updated faster
+    pub async fn get_lite_order_book(&self, symbol: &str) -> Result<Orderbook, OKXClientError> {
+        self.fetch_order_book("api/v5/market/books-lite", &[("instId", symbol)])
+            .await?
+            .parse_to_orderbook()
+    }
+
+    /// Fetches the ticker of `symbol`
+    pub async fn get_ticker(&self, symbol: &str) -> Result<Ticker, OKXClientError> {
+        self.get::<Ticker>("api/v5/market/ticker", &[("instId", symbol)])
+            .await?
+            .into_iter()
+            .next()
+            .ok_or_else(|| OKXClientError::UnexpectedResponseStructure("Empty 'data' array".into()))
+    }
+
+    /// Fetches the tickers of every instrument of `inst_type` (`SPOT`, `SWAP`, `FUTURES`, `OPTION`)
+    pub async fn get_tickers(&self, inst_type: &str) -> Result<Vec<Ticker>, OKXClientError> {
+        self.get("api/v5/market/tickers", &[("instType", inst_type)])
+            .await
+    }
+
+    /// Fetches the latest candles of `symbol`, newest first, up to `CANDLES_PAGE_SIZE`
+    pub async fn get_candles(&self, symbol: &str, bar: Bar) -> Result<Vec<Candle>, OKXClientError> {
+        self.fetch_candles(false, symbol, bar, None, None).await
+    }
+
+    /// Fetches candles of `symbol` opened before `after` (in milliseconds), newest first
+    ///
+    /// Reaches back to the start of OKX's history, `HISTORY_CANDLES_PAGE_SIZE` at a time.
+    pub async fn get_history_candles(
+        &self,
+        symbol: &str,
+        bar: Bar,
+        after: u64,
+    ) -> Result<Vec<Candle>, OKXClientError> {
+        self.fetch_candles(true, symbol, bar, Some(after), None)
+            .await
+    }
+
+    /// Streams every candle of `symbol` opened between `start` and `end`
+    /// (inclusive, in milliseconds), newest first
+    ///
+    /// Pages through `candles` while it has data, then `history-candles`,
+    /// using the `after` cursor. Each page goes through the rate limiter and
+    /// retry policy, so long downloads pace themselves.
+    pub fn candles_between(
+        &self,
+        symbol: &str,
+        bar: Bar,
+        start: u64,
+        end: u64,
+    ) -> impl Stream<Item = Result<Candle, OKXClientError>> + '_ {
+        let cursor = CandleCursor {
+            symbol: symbol.to_string(),
+            bar,
+            start,
+            after: end.saturating_add(1),
+            history: false,
+            done: start > end,
+        };
+        stream::try_unfold(cursor, move |mut cursor| async move {
+            while !cursor.done {
+                let page = self
+                    .fetch_candles(
+                        cursor.history,
+                        &cursor.symbol,
+                        cursor.bar,
+                        Some(cursor.after),
+                        cursor.start.checked_sub(1),
+                    )
+                    .await?;
+                let Some(oldest) = page.last() else {
+                    // `candles` ran out of recent bars; continue further back in history
+                    cursor.done = cursor.history;
+                    cursor.history = true;
+                    continue;
+                };
+                cursor.after = oldest.ts;
+                cursor.done = oldest.ts <= cursor.start;
+                return Ok(Some((page, cursor)));
+            }
+            Ok::<_, OKXClientError>(None)
+        })
+        .map_ok(|page| stream::iter(page.into_iter().map(Ok)))
+        .try_flatten()
+    }
+
+    /// Downloads every candle of `symbol` opened between `start` and `end`, oldest first
+    pub async fn get_candles_between(
+        &self,
+        symbol: &str,
+        bar: Bar,
+        start: u64,
+        end: u64,
+    ) -> Result<Vec<Candle>, OKXClientError> {
+        let mut candles: Vec<Candle> = self
+            .candles_between(symbol, bar, start, end)
+            .try_collect()
+            .await?;
+        candles.reverse();
+        Ok(candles)
+    }
+
+    /// Fetches the latest `limit` trades of `symbol`, newest first, at most `MAX_TRADES`
+    pub async fn get_trades(&self, symbol: &str, limit: u32) -> Result<Vec<Trade>, OKXClientError> {
+        let limit = Self::bounded_param("limit", limit, MAX_TRADES)?;
+        self.get(
+            "api/v5/market/trades",
+            &[("instId", symbol), ("limit", &limit)],
+        )
+        .await
+    }
+
+    /// Fetches up to `HISTORY_TRADES_PAGE_SIZE` trades of `symbol` older than `after`,
+    /// or the latest ones without a cursor, newest first
+    pub async fn get_history_trades(
+        &self,
+        symbol: &str,
+        after: Option<&TradeCursor>,
+    ) -> Result<Vec<Trade>, OKXClientError> {
+        let limit = HISTORY_TRADES_PAGE_SIZE.to_string();
+        let mut query = vec![("instId", symbol), ("limit", limit.as_str())];
+        let value = after.map(TradeCursor::value);
+        if let (Some(after), Some(value)) = (after, &value) {
+            query.push(("type", after.kind()));
+            query.push(("after", value));
+        }
+        self.get("api/v5/market/history-trades", &query).await
+    }
+
+    /// Streams every trade of `symbol` between `start` and `end`
+    /// (inclusive, in milliseconds), newest first
+    ///
+    /// The first page is found by timestamp and later pages by trade ID, so
+    /// trades sharing a millisecond across a page boundary are not skipped.
+    pub fn trades_between(
+        &self,
+        symbol: &str,
+        start: u64,
+        end: u64,
+    ) -> impl Stream<Item = Result<Trade, OKXClientError>> + '_ {
+        let cursor = TradeCursorState {
+            symbol: symbol.to_string(),
+            start,
+            after: TradeCursor::Timestamp(end.saturating_add(1)),
+            done: start > end,
+        };
+        stream::try_unfold(cursor, move |mut cursor| async move {
+            if cursor.done {
+                return Ok(None);
+            }
+            let mut page = self
+                .get_history_trades(&cursor.symbol, Some(&cursor.after))
+                .await?;
+            let Some(oldest) = page.last() else {
+                return Ok(None);
+            };
+            cursor.after = TradeCursor::TradeId(oldest.trade_id.clone());
+            cursor.done = oldest.ts < cursor.start;
+            page.retain(|trade| trade.ts >= cursor.start);
+            Ok::<_, OKXClientError>(Some((page, cursor)))
+        })
+        .map_ok(|page| stream::iter(page.into_iter().map(Ok)))
+        .try_flatten()
+    }
+
+    /// Downloads every trade of `symbol` between `start` and `end`, oldest first
+    pub async fn get_trades_between(
+        &self,
+        symbol: &str,
+        start: u64,
+        end: u64,
+    ) -> Result<Vec<Trade>, OKXClientError> {
+        let mut trades: Vec<Trade> = self
+            .trades_between(symbol, start, end)
+            .try_collect()
+            .await?;
+        trades.reverse();
+        Ok(trades)
+    }
+
+    async fn fetch_candles(
+        &self,
+        history: bool,
+        symbol: &str,
+        bar: Bar,
+        after: Option<u64>,
+        before: Option<u64>,
+    ) -> Result<Vec<Candle>, OKXClientError> {
+        let (path, limit) = if history {
+            ("api/v5/market/history-candles", HISTORY_CANDLES_PAGE_SIZE)
+        } else {
+            ("api/v5/market/candles", CANDLES_PAGE_SIZE)
+        };
+        let after = after.map(|after| after.to_string());
+        let before = before.map(|before| before.to_string());
+        let limit = limit.to_string();
+        let mut query = vec![("instId", symbol), ("bar", bar.as_str()), ("limit", &limit)];
+        if let Some(after) = &after {
+            query.push(("after", after));
+        }
+        if let Some(before) = &before {
+            query.push(("before", before));
+        }
+        self.get(path, &query).await
+    }
+
+    async fn fetch_order_book(
+        &self,
+        path: &str,
+        query: &[(&str, &str)],
+    ) -> Result<RawOrderbook, OKXClientError> {
+        self.get::<RawOrderbook>(path, query)
+            .await?
+            .into_iter()
+            .next()
+            .ok_or_else(|| OKXClientError::UnexpectedResponseStructure("Empty 'data' array".into()))
+    }
+
+    fn bounded_param(name: &str, value: u32, max: u32) -> Result<String, OKXClientError> {
+        if value == 0 || value > max {
+            return Err(OKXClientError::InvalidParameter(format!(
+                "{} must be between 1 and {}, got {}",
+                name, max, value
+            )));
+        }
+        Ok(value.to_string())
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use wiremock::matchers::{method, path};
+    use crate::client::rate_limit::{RateLimit, RateLimitMode};
+    use serde_json::json;
+    use wiremock::matchers::{header, method, path, query_param};
     use wiremock::{Mock, MockServer, ResponseTemplate};
 
     #[tokio::test]
@@ -116,8 +701,484 @@ mod tests {
 
         assert_eq!(orderbook.asks.len(), 1);
         assert_eq!(orderbook.bids.len(), 1);
-        assert_eq!(orderbook.asks[0], (50000.0, 1.0));
-        assert_eq!(orderbook.bids[0], (49999.0, 1.0));
+        assert_eq!(
+            orderbook.best_ask(),
+            Some(("50000".parse().unwrap(), "1".parse().unwrap()))
+        );
+        assert_eq!(
+            orderbook.best_bid(),
+            Some(("49999".parse().unwrap(), "1".parse().unwrap()))
+        );
         assert_eq!(orderbook.ts, 1719335318504);
+        assert_eq!(orderbook.asks.best_level().unwrap().num_orders, 7);
+        assert_eq!(orderbook.bids.best_level().unwrap().num_orders, 6);
+    }
+
+    #[tokio::test]
+    async fn test_signed_requests_carry_access_headers() {
+        let mock_server = MockServer::start().await;
+
+        Mock::given(path("/api/v5/trade/order"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "code": "0",
+                "msg": "",
+                "data": []
+            })))
+            .mount(&mock_server)
+            .await;
+
+        let credentials = Credentials::new("key", "secret", "passphrase");
+        let client = OKXRestClient::new(&mock_server.uri())
+            .unwrap()
+            .with_credentials(credentials.clone());
+        client
+            .get::<Value>(
+                "/api/v5/trade/order",
+                &[("instId", "BTC-USDT"), ("ordId", "1")],
+            )
+            .await
+            .unwrap();
+        client
+            .post::<_, Value>(
+                "/api/v5/trade/order",
+                &serde_json::json!({"instId": "BTC-USDT"}),
+            )
+            .await
+            .unwrap();
+
+        let requests = mock_server.received_requests().await.unwrap();
+        let expected = [
+            ("GET", "/api/v5/trade/order?instId=BTC-USDT&ordId=1", ""),
+            ("POST", "/api/v5/trade/order", r#"{"instId":"BTC-USDT"}"#),
+        ];
+        for (request, (method, request_path, body)) in requests.iter().zip(expected) {
+            let header = |name: &str| request.headers.get(name).unwrap().to_str().unwrap();
+            assert_eq!(header("OK-ACCESS-KEY"), "key");
+            assert_eq!(header("OK-ACCESS-PASSPHRASE"), "passphrase");
+            assert_eq!(
+                header("OK-ACCESS-SIGN"),
+                credentials.sign(header("OK-ACCESS-TIMESTAMP"), method, request_path, body)
+            );
+            assert_eq!(String::from_utf8_lossy(&request.body), body);
+            assert!(request.headers.get("x-simulated-trading").is_none());
+        }
+    }
+
+    #[tokio::test]
+    async fn test_demo_environment_adds_simulated_trading_header() {
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("GET"))
+            .and(path("/api/v5/market/books"))
+            .and(header("x-simulated-trading", "1"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "code": "0",
+                "msg": "",
+                "data": [{"asks": [], "bids": [], "ts": "1719335318504"}]
+            })))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let client = OKXRestClient::new(&mock_server.uri())
+            .unwrap()
+            .with_environment(Environment::Demo);
+        client.get_order_book("BTC-USDT").await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_error_code_becomes_api_error() {
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("GET"))
+            .and(path("/api/v5/market/books"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "code": "51001",
+                "msg": "Instrument ID does not exist",
+                "data": []
+            })))
+            .mount(&mock_server)
+            .await;
+
+        let client = OKXRestClient::new(&mock_server.uri()).unwrap();
+        let error = client.get_order_book("NOPE-USDT").await.unwrap_err();
+
+        assert!(matches!(
+            &error,
+            OKXClientError::Api { code, msg } if code == "51001" && msg == "Instrument ID does not exist"
+        ));
+        assert_eq!(error.api_error_kind(), Some(ApiErrorKind::InvalidParameter));
+        assert!(!error.api_error_kind().unwrap().is_retryable());
+    }
+
+    #[tokio::test]
+    async fn test_error_data_is_not_decoded() {
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("GET"))
+            .and(path("/api/v5/market/books"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "code": "50014",
+                "msg": "Parameter instId can not be empty",
+                "data": [{"unexpected": true}]
+            })))
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/api/v5/trade/batch-orders"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "code": "2",
+                "msg": "",
+                "data": [
+                    {"clOrdId": "a", "ordId": "1", "sCode": "0", "sMsg": "", "ts": "1695190491421"},
+                    {"clOrdId": "b", "ordId": "", "sCode": "51008", "sMsg": "Insufficient balance", "ts": "1695190491421"}
+                ]
+            })))
+            .mount(&mock_server)
+            .await;
+
+        let client = OKXRestClient::new(&mock_server.uri()).unwrap();
+        let error = client.get_order_book("").await.unwrap_err();
+        assert!(matches!(&error, OKXClientError::Api { code, .. } if code == "50014"));
+
+        let error = client
+            .post::<_, Value>("/api/v5/trade/batch-orders", &serde_json::json!([]))
+            .await
+            .unwrap_err();
+        let OKXClientError::ItemsFailed { code, data } = &error else {
+            panic!("expected per-item errors, got {error:?}");
+        };
+        assert_eq!(code, "2");
+        assert_eq!(data[0]["ordId"], "1");
+        assert_eq!(
+            error.to_string(),
+            "OKX API error 2: 51008: Insufficient balance"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_rate_limiter_rejects_before_sending() {
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("POST"))
+            .and(path("/api/v5/trade/order"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "code": "0",
+                "msg": "",
+                "data": []
+            })))
+            .expect(2)
+            .mount(&mock_server)
+            .await;
+
+        let client = OKXRestClient::new(&mock_server.uri())
+            .unwrap()
+            .with_rate_limiter(
+                RateLimiter::default()
+                    .with_mode(RateLimitMode::Reject)
+                    .with_limit(
+                        "/api/v5/trade/order",
+                        RateLimit::per_instrument(1, Duration::from_secs(60)),
+                    ),
+            );
+        let order = |inst_id: &str| serde_json::json!({"instId": inst_id});
+
+        client
+            .post::<_, Value>("api/v5/trade/order", &order("BTC-USDT"))
+            .await
+            .unwrap();
+        let error = client
+            .post::<_, Value>("api/v5/trade/order", &order("BTC-USDT"))
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            &error,
+            OKXClientError::RateLimited { endpoint, .. } if endpoint == "/api/v5/trade/order"
+        ));
+        assert_eq!(error.api_error_kind(), Some(ApiErrorKind::RateLimited));
+        client
+            .post::<_, Value>("api/v5/trade/order", &order("ETH-USDT"))
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_transient_failures_are_retried_for_gets_only() {
+        let mock_server = MockServer::start().await;
+        let busy = json!({"code": "50013", "msg": "Systems are busy", "data": []});
+
+        Mock::given(method("GET"))
+            .and(path("/api/v5/market/books"))
+            .respond_with(ResponseTemplate::new(502).set_body_string("Bad Gateway"))
+            .up_to_n_times(1)
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/api/v5/market/books"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(&busy))
+            .up_to_n_times(1)
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/api/v5/market/books"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "code": "0",
+                "msg": "",
+                "data": [{"asks": [], "bids": [], "ts": "1719335318504"}]
+            })))
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/api/v5/trade/order"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(&busy))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/api/v5/public/time"))
+            .respond_with(ResponseTemplate::new(503))
+            .expect(3)
+            .mount(&mock_server)
+            .await;
+
+        let client = OKXRestClient::new(&mock_server.uri())
+            .unwrap()
+            .with_retry(RetryConfig {
+                max_retries: 2,
+                initial_delay: Duration::from_millis(10),
+                ..Default::default()
+            });
+
+        let orderbook = client.get_order_book("BTC-USDT").await.unwrap();
+        assert_eq!(orderbook.ts, 1719335318504);
+
+        let error = client
+            .post::<_, Value>("api/v5/trade/order", &json!({"instId": "BTC-USDT"}))
+            .await
+            .unwrap_err();
+        assert!(matches!(&error, OKXClientError::Api { code, .. } if code == "50013"));
+
+        let error = client
+            .get::<Value>("api/v5/public/time", &[])
+            .await
+            .unwrap_err();
+        match error {
+            OKXClientError::RetriesExhausted { attempts, source } => {
+                assert_eq!(attempts, 3);
+                assert!(matches!(
+                    *source,
+                    OKXClientError::HttpStatus { status: 503, .. }
+                ));
+            }
+            other => panic!("unexpected error: {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_order_book_depth_and_full_book() {
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("GET"))
+            .and(path("/api/v5/market/books"))
+            .and(query_param("sz", "400"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "code": "0",
+                "msg": "",
+                "data": [{
+                    "asks": [["50001", "2", "0", "3"], ["50000", "1", "0", "7"]],
+                    "bids": [["49999", "1", "0", "6"]],
+                    "ts": "1719335318504"
+                }]
+            })))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/api/v5/market/books-full"))
+            .and(query_param("sz", "5000"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "code": "0",
+                "msg": "",
+                "data": [{
+                    "asks": [["50000", "1", "7"]],
+                    "bids": [["49999", "1", "6"]],
+                    "ts": "1719335318504"
+                }]
+            })))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let client = OKXRestClient::new(&mock_server.uri()).unwrap();
+
+        let orderbook = client
+            .get_order_book_with_depth("BTC-USDT", 400)
+            .await
+            .unwrap();
+        assert_eq!(orderbook.asks.len(), 2);
+        assert_eq!(orderbook.best_ask().unwrap().0, "50000".parse().unwrap());
+
+        let full = client.get_full_order_book("BTC-USDT", 5000).await.unwrap();
+        let best_ask = full.asks.best_level().unwrap();
+        assert_eq!((best_ask.liquidated_orders, best_ask.num_orders), (0, 7));
+        assert_eq!(full.bids.best_level().unwrap().num_orders, 6);
+
+        assert!(matches!(
+            client.get_order_book_with_depth("BTC-USDT", 401).await,
+            Err(OKXClientError::InvalidParameter(_))
+        ));
+        assert!(matches!(
+            client.get_full_order_book("BTC-USDT", 0).await,
+            Err(OKXClientError::InvalidParameter(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_tickers() {
+        let mock_server = MockServer::start().await;
+        let ticker = |inst_id: &str, last: &str| {
+            json!({
+                "instType": "SPOT", "instId": inst_id, "last": last, "lastSz": "0.1",
+                "askPx": "", "askSz": "", "bidPx": "", "bidSz": "",
+                "open24h": "1", "high24h": "2", "low24h": "0.5",
+                "volCcy24h": "100", "vol24h": "50", "ts": "1597026383085"
+            })
+        };
+
+        Mock::given(method("GET"))
+            .and(path("/api/v5/market/ticker"))
+            .and(query_param("instId", "BTC-USDT"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "code": "0", "msg": "", "data": [ticker("BTC-USDT", "50000")]
+            })))
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/api/v5/market/tickers"))
+            .and(query_param("instType", "SPOT"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "code": "0",
+                "msg": "",
+                "data": [ticker("BTC-USDT", "50000"), ticker("ETH-USDT", "3000")]
+            })))
+            .mount(&mock_server)
+            .await;
+
+        let client = OKXRestClient::new(&mock_server.uri()).unwrap();
+
+        let btc = client.get_ticker("BTC-USDT").await.unwrap();
+        assert_eq!(btc.last, Some("50000".parse().unwrap()));
+        assert_eq!(btc.bid_px, None);
+
+        let tickers = client.get_tickers("SPOT").await.unwrap();
+        assert_eq!(tickers.len(), 2);
+        assert_eq!(tickers[1].inst_id, "ETH-USDT");
+    }
+
+    #[tokio::test]
+    async fn test_candles_between_walks_candles_then_history() {
+        let mock_server = MockServer::start().await;
+        let candle = |ts: u64| {
+            json!([
+                ts.to_string(),
+                "1",
+                "2",
+                "0.5",
+                "1.5",
+                "10",
+                "15",
+                "15",
+                "1"
+            ])
+        };
+        let page = |candles: Vec<Value>| {
+            ResponseTemplate::new(200)
+                .set_body_json(json!({"code": "0", "msg": "", "data": candles}))
+        };
+
+        Mock::given(path("/api/v5/market/candles"))
+            .and(query_param("after", "780001"))
+            .and(query_param("before", "599999"))
+            .and(query_param("bar", "1m"))
+            .and(query_param("limit", "300"))
+            .respond_with(page(vec![candle(780000), candle(720000)]))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+        Mock::given(path("/api/v5/market/candles"))
+            .and(query_param("after", "720000"))
+            .respond_with(page(vec![]))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+        Mock::given(path("/api/v5/market/history-candles"))
+            .and(query_param("after", "720000"))
+            .and(query_param("limit", "100"))
+            .respond_with(page(vec![candle(660000), candle(600000)]))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let client = OKXRestClient::new(&mock_server.uri()).unwrap();
+        let candles = client
+            .get_candles_between("BTC-USDT", Bar::Minute1, 600000, 780000)
+            .await
+            .unwrap();
+
+        let times: Vec<u64> = candles.iter().map(|candle| candle.ts).collect();
+        assert_eq!(times, [600000, 660000, 720000, 780000]);
+        assert!(candles.iter().all(|candle| candle.confirmed));
+    }
+
+    #[tokio::test]
+    async fn test_trades_between_pages_by_trade_id() {
+        let mock_server = MockServer::start().await;
+        let trade = |trade_id: u64, ts: u64| {
+            json!({
+                "instId": "BTC-USDT", "tradeId": trade_id.to_string(), "px": "50000",
+                "sz": "0.1", "side": "buy", "ts": ts.to_string()
+            })
+        };
+        let page = |trades: Vec<Value>| {
+            ResponseTemplate::new(200)
+                .set_body_json(json!({"code": "0", "msg": "", "data": trades}))
+        };
+
+        Mock::given(path("/api/v5/market/trades"))
+            .and(query_param("limit", "2"))
+            .respond_with(page(vec![trade(5, 3000)]))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+        Mock::given(path("/api/v5/market/history-trades"))
+            .and(query_param("type", "2"))
+            .and(query_param("after", "2001"))
+            .respond_with(page(vec![trade(4, 2000), trade(3, 1000)]))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+        Mock::given(path("/api/v5/market/history-trades"))
+            .and(query_param("type", "1"))
+            .and(query_param("after", "3"))
+            .respond_with(page(vec![trade(2, 1000), trade(1, 999)]))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let client = OKXRestClient::new(&mock_server.uri()).unwrap();
+
+        let latest = client.get_trades("BTC-USDT", 2).await.unwrap();
+        assert_eq!(latest[0].trade_id, "5");
+        assert!(matches!(
+            client.get_trades("BTC-USDT", 501).await,
+            Err(OKXClientError::InvalidParameter(_))
+        ));
+
+        let trades = client
+            .get_trades_between("BTC-USDT", 1000, 2000)
+            .await
+            .unwrap();
+        let ids: Vec<&str> = trades.iter().map(|trade| trade.trade_id.as_str()).collect();
+        assert_eq!(ids, ["2", "3", "4"]);
     }
 }
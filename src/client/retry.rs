@@ -0,0 +1,77 @@
+use std::time::Duration;
+
+/// Exponential backoff policy for REST requests that fail transiently
+///
+/// A request is retried after transport errors, HTTP 429/5xx responses and
+/// retryable OKX codes (rate limited or system busy, e.g. `50001`, `50013`).
+/// The delay before retry `n` (starting at 1) is
+/// `initial_delay * multiplier^(n - 1)`, capped at `max_delay`.
+///
+/// Only GET requests are retried by default: retrying a POST that OKX
+/// received but failed to answer could place or cancel an order twice.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RetryConfig {
+    /// Retries after the first attempt, 0 to never retry
+    pub max_retries: u32,
+    /// Delay before the first retry
+    pub initial_delay: Duration,
+    /// Upper bound for the delay
+    pub max_delay: Duration,
+    /// Factor applied to the delay after each failed retry
+    pub multiplier: f64,
+    /// Also retry POST requests, which are not idempotent
+    pub retry_posts: bool,
+}
+
+impl Default for RetryConfig {
+    fn default() -> Self {
+        RetryConfig {
+            max_retries: 3,
+            initial_delay: Duration::from_millis(250),
+            max_delay: Duration::from_secs(5),
+            multiplier: 2.0,
+            retry_posts: false,
+        }
+    }
+}
+
+impl RetryConfig {
+    /// Policy that never retries
+    pub fn disabled() -> Self {
+        RetryConfig {
+            max_retries: 0,
+            ..Default::default()
+        }
+    }
+
+    pub fn with_post_retries(mut self, retry_posts: bool) -> Self {
+        self.retry_posts = retry_posts;
+        self
+    }
+
+    /// Returns the delay to wait before retry `retry`
+    pub fn delay(&self, retry: u32) -> Duration {
+        let exponent = retry.saturating_sub(1).min(i32::MAX as u32) as i32;
+        let delay = self.initial_delay.as_secs_f64() * self.multiplier.powi(exponent);
+        Duration::from_secs_f64(delay.min(self.max_delay.as_secs_f64()).max(0.0))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_delay_grows_exponentially_and_is_capped() {
+        let config = RetryConfig {
+            initial_delay: Duration::from_millis(100),
+            max_delay: Duration::from_millis(500),
+            ..Default::default()
+        };
+        assert_eq!(config.delay(1), Duration::from_millis(100));
+        assert_eq!(config.delay(3), Duration::from_millis(400));
+        assert_eq!(config.delay(10), Duration::from_millis(500));
+        assert!(!config.retry_posts);
+        assert_eq!(RetryConfig::disabled().max_retries, 0);
+    }
+}
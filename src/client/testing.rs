@@ -0,0 +1,17 @@
+//! Local WebSocket servers standing in for OKX in tests
+
+use tokio::net::{TcpListener, TcpStream};
+use tokio_tungstenite::{accept_async, WebSocketStream};
+
+/// Binds a listener on a free local port and returns it with its `ws://` URL
+pub(crate) async fn ws_server() -> (TcpListener, String) {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("ws://{}", listener.local_addr().unwrap());
+    (listener, url)
+}
+
+/// Accepts the next connection and completes its WebSocket handshake
+pub(crate) async fn accept(listener: &TcpListener) -> WebSocketStream<TcpStream> {
+    let (stream, _) = listener.accept().await.unwrap();
+    accept_async(stream).await.unwrap()
+}
@@ -1,62 +1,705 @@
-use futures_util::{SinkExt, StreamExt};
+use crate::client::auth::{unix_timestamp, Credentials};
+use crate::client::connection::{Command, Subscriptions, WsConnection};
+use crate::client::environment::Environment;
+use crate::client::heartbeat::HeartbeatConfig;
+use crate::client::reconnect::{ConnectLimiter, ReconnectConfig};
+use crate::models::ws_message::{ChannelArg, WsEvent, WsMessage};
+use futures_util::{Sink, SinkExt, Stream, StreamExt};
+use std::sync::{Arc, OnceLock};
+use std::time::Duration;
 use thiserror::Error;
 use tokio::sync::mpsc;
-use tokio_tungstenite::{connect_async, tungstenite::protocol::Message};
+use tokio::time::Instant;
+use tokio_tungstenite::connect_async;
+use tokio_tungstenite::tungstenite::{self, protocol::Message};
+
+/// Time allowed for OKX to answer a `login` request
+const LOGIN_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Time allowed for OKX to answer an operation sent through a [`WsConnection`]
+const DEFAULT_REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
 
 #[derive(Error, Debug)]
 pub enum WebSocketError {
     #[error("WebSocket connection error: {0}")]
-    ConnectionError(#[from] tokio_tungstenite::tungstenite::Error),
+    ConnectionError(Box<tungstenite::Error>),
     #[error("Channel send error: {0}")]
-    ChannelSendError(#[from] tokio::sync::mpsc::error::SendError<String>),
+    ChannelSendError(#[from] Box<tokio::sync::mpsc::error::SendError<WsMessage>>),
+    #[error("Failed to decode message: {0}")]
+    DeserializationError(#[from] serde_json::Error),
+    #[error("No pong received within {0:?}")]
+    PongTimeout(Duration),
+    #[error("Login failed {code}: {msg}")]
+    LoginFailed { code: String, msg: String },
+    #[error("No login response within {0:?}")]
+    LoginTimeout(Duration),
+    #[error("Request rejected {code}: {msg}")]
+    Rejected { code: String, msg: String },
+    #[error("WebSocket connection closed")]
+    ConnectionClosed,
+    #[error("All pooled connections are full")]
+    PoolExhausted,
+    #[error("Order rejected {s_code}: {s_msg}")]
+    OrderRejected { s_code: String, s_msg: String },
+    #[error("No response within {0:?}")]
+    RequestTimeout(Duration),
+}
+
+impl WebSocketError {
+    /// Returns `true` if reconnecting cannot help, e.g. rejected credentials
+    pub fn is_fatal(&self) -> bool {
+        matches!(
+            self,
+            WebSocketError::ChannelSendError(_) | WebSocketError::LoginFailed { .. }
+        )
+    }
 }
 
+impl From<tungstenite::Error> for WebSocketError {
+    fn from(e: tungstenite::Error) -> Self {
+        WebSocketError::ConnectionError(Box::new(e))
+    }
+}
+
+#[derive(Clone)]
 pub struct OKXWebSocketClient {
     url: String,
+    reconnect: ReconnectConfig,
+    heartbeat: HeartbeatConfig,
+    credentials: Option<Credentials>,
+    request_timeout: Duration,
+    connect_limiter: Option<Arc<ConnectLimiter>>,
 }
 
 impl OKXWebSocketClient {
     pub fn new(url: &str) -> Self {
         OKXWebSocketClient {
             url: url.to_string(),
+            reconnect: ReconnectConfig::default(),
+            heartbeat: HeartbeatConfig::default(),
+            credentials: None,
+            request_timeout: DEFAULT_REQUEST_TIMEOUT,
+            connect_limiter: None,
         }
     }
 
+    /// Sets the reconnection policy used when the connection drops
+    pub fn with_reconnect(mut self, reconnect: ReconnectConfig) -> Self {
+        self.reconnect = reconnect;
+        self
+    }
+
+    /// Sets the keepalive policy used to detect idle or dead connections
+    pub fn with_heartbeat(mut self, heartbeat: HeartbeatConfig) -> Self {
+        self.heartbeat = heartbeat;
+        self
+    }
+
+    /// Connects to `environment`, moving an OKX URL to its WebSocket host
+    ///
+    /// Demo trading is served from `wspap.okx.com`. URLs that do not point
+    /// at an OKX host are left unchanged.
+    pub fn with_environment(mut self, environment: Environment) -> Self {
+        self.url = environment.ws_url(&self.url);
+        self
+    }
+
+    /// Sets how long [`WsConnection`] operations such as order placement wait for a response
+    pub fn with_request_timeout(mut self, request_timeout: Duration) -> Self {
+        self.request_timeout = request_timeout;
+        self
+    }
+
+    /// Paces every connection attempt, reconnections included, through `limiter`
+    pub(crate) fn with_connect_limiter(mut self, limiter: Arc<ConnectLimiter>) -> Self {
+        self.connect_limiter = Some(limiter);
+        self
+    }
+
+    /// Logs in with `credentials` on every connection before subscribing
+    ///
+    /// Required for private channels, which are served on the
+    /// `/ws/v5/private` endpoint.
+    pub fn with_credentials(mut self, credentials: Credentials) -> Self {
+        self.credentials = Some(credentials);
+        self
+    }
+
     pub async fn subscribe_to_order_book(
         &self,
         symbol: &str,
-        tx: mpsc::Sender<String>,
+        tx: mpsc::Sender<WsMessage>,
     ) -> Result<(), WebSocketError> {
-        let (ws_stream, _) = connect_async(&self.url).await?;
-        println!("WebSocket handshake has been successfully completed");
+        self.subscribe_to_channel("books", symbol, tx).await
+    }
+
+    /// Subscribes to the `tickers` channel for `symbol`
+    ///
+    /// Pushes decode into [`Ticker`](crate::models::Ticker) with `WsPush::decode`.
+    pub async fn subscribe_to_tickers(
+        &self,
+        symbol: &str,
+        tx: mpsc::Sender<WsMessage>,
+    ) -> Result<(), WebSocketError> {
+        self.subscribe_to_channel("tickers", symbol, tx).await
+    }
+
+    /// Subscribes to the `trades` channel for `symbol`, on the public endpoint
+    ///
+    /// Trades at the same price may be aggregated into one print, with
+    /// [`Trade::count`](crate::models::Trade::count) above 1.
+    pub async fn subscribe_to_trades(
+        &self,
+        symbol: &str,
+        tx: mpsc::Sender<WsMessage>,
+    ) -> Result<(), WebSocketError> {
+        self.subscribe_to_channel("trades", symbol, tx).await
+    }
+
+    /// Subscribes to the `trades-all` channel for `symbol`, one push per trade
+    ///
+    /// OKX serves this channel on the business endpoint, see
+    /// [`Environment::business_ws_url`].
+    pub async fn subscribe_to_all_trades(
+        &self,
+        symbol: &str,
+        tx: mpsc::Sender<WsMessage>,
+    ) -> Result<(), WebSocketError> {
+        self.subscribe_to_channel("trades-all", symbol, tx).await
+    }
+
+    /// Subscribes to the private `orders` channel for `inst_type` (`ANY` for all)
+    ///
+    /// Requires [`with_credentials`](Self::with_credentials). Pushes can be
+    /// fed to an [`OrderTracker`](crate::models::OrderTracker).
+    pub async fn subscribe_to_orders(
+        &self,
+        inst_type: &str,
+        tx: mpsc::Sender<WsMessage>,
+    ) -> Result<(), WebSocketError> {
+        self.subscribe(vec![ChannelArg::instrument_type("orders", inst_type)], tx)
+            .await
+    }
+
+    /// Subscribes to `channel` for `symbol` and forwards every decoded message to `tx`
+    pub async fn subscribe_to_channel(
+        &self,
+        channel: &str,
+        symbol: &str,
+        tx: mpsc::Sender<WsMessage>,
+    ) -> Result<(), WebSocketError> {
+        self.subscribe(vec![ChannelArg::instrument(channel, symbol)], tx)
+            .await
+    }
+
+    /// Subscribes to all `args` on one connection and forwards every decoded message to `tx`
+    ///
+    /// When the connection drops it is re-established according to the
+    /// reconnection policy, all subscriptions are replayed, and
+    /// [`WsMessage::Reconnected`] is sent before any new data so consumers
+    /// can discard state built from the previous connection. Returns once
+    /// the policy gives up, or with an error if `tx` is closed.
+    pub async fn subscribe(
+        &self,
+        args: Vec<ChannelArg>,
+        tx: mpsc::Sender<WsMessage>,
+    ) -> Result<(), WebSocketError> {
+        self.run(Subscriptions::new(args, None), &tx).await
+    }
+
+    /// Opens a long-lived connection and returns a handle to manage its subscriptions
+    ///
+    /// The connection starts without channels; add them with
+    /// [`WsConnection::subscribe`]. Every decoded message is forwarded to
+    /// `tx`, including the acknowledgements the handle waits for.
+    pub fn connect(self, tx: mpsc::Sender<WsMessage>) -> WsConnection {
+        let (commands, receiver) = mpsc::channel(32);
+        let request_timeout = self.request_timeout;
+        let fatal = Arc::new(OnceLock::new());
+        let task = {
+            let fatal = Arc::clone(&fatal);
+            tokio::spawn(async move {
+                let result = self
+                    .run(Subscriptions::new(Vec::new(), Some(receiver)), &tx)
+                    .await;
+                match result {
+                    Err(e) if e.is_fatal() => {
+                        let _ = fatal.set(e);
+                        Ok(())
+                    }
+                    result => result,
+                }
+            })
+        };
+        WsConnection::new(commands, task, request_timeout, fatal)
+    }
+
+    /// Keeps a connection alive according to the reconnection policy
+    async fn run(
+        &self,
+        mut subscriptions: Subscriptions,
+        tx: &mpsc::Sender<WsMessage>,
+    ) -> Result<(), WebSocketError> {
+        let mut attempt = 0;
+        loop {
+            let reconnect_attempt = (attempt > 0).then_some(attempt);
+            let (established, result) = self
+                .run_session(&mut subscriptions, tx, reconnect_attempt)
+                .await;
+            subscriptions.connection_lost();
+            if result.as_ref().is_err_and(WebSocketError::is_fatal) {
+                return result;
+            }
+
+            if established {
+                attempt = 0;
+            }
+            attempt += 1;
+            if !self.reconnect.should_retry(attempt) {
+                return result;
+            }
+            tokio::time::sleep(self.reconnect.delay(attempt)).await;
+        }
+    }
+
+    /// Runs a single connection until it closes or fails
+    ///
+    /// Returns whether the subscriptions were sent, alongside the outcome.
+    async fn run_session(
+        &self,
+        subscriptions: &mut Subscriptions,
+        tx: &mpsc::Sender<WsMessage>,
+        reconnect_attempt: Option<u32>,
+    ) -> (bool, Result<(), WebSocketError>) {
+        if let Some(limiter) = &self.connect_limiter {
+            limiter.wait().await;
+        }
+        let (ws_stream, _) = match connect_async(&self.url).await {
+            Ok(connection) => connection,
+            Err(e) => return (false, Err(e.into())),
+        };
 
         let (mut write, mut read) = ws_stream.split();
 
-        let subscribe_message = serde_json::json!({
-            "op": "subscribe",
-            "args": [{
-                "channel": "books",
-                "instId": symbol
-            }]
+        if let Some(credentials) = &self.credentials {
+            if let Err(e) = Self::login(credentials, &mut write, &mut read, tx).await {
+                return (false, Err(e));
+            }
+        }
+
+        if !subscriptions.args().is_empty() {
+            let subscribe_message = serde_json::json!({
+                "op": "subscribe",
+                "args": subscriptions.args()
+            });
+
+            if let Err(e) = write
+                .send(Message::Text(subscribe_message.to_string()))
+                .await
+            {
+                return (false, Err(e.into()));
+            }
+        }
+
+        if let Some(attempts) = reconnect_attempt {
+            if let Err(e) = tx.send(WsMessage::Reconnected { attempts }).await {
+                return (true, Err(Box::new(e).into()));
+            }
+        }
+
+        let result = async {
+            let mut last_received = Instant::now();
+            let mut ping_sent: Option<Instant> = None;
+            loop {
+                let deadline = match ping_sent {
+                    Some(sent) => sent + self.heartbeat.pong_timeout,
+                    None => last_received + self.heartbeat.interval,
+                };
+                let input = tokio::select! {
+                    message = read.next() => Input::Frame(message),
+                    command = subscriptions.next_command() => Input::Command(command),
+                    _ = tokio::time::sleep_until(deadline) => Input::Idle,
+                };
+                let message = match input {
+                    Input::Frame(Some(message)) => message?,
+                    Input::Frame(None) => break,
+                    Input::Command(Some(command)) => {
+                        if let Some(request) = subscriptions.request(command) {
+                            write.send(Message::Text(request)).await?;
+                        }
+                        continue;
+                    }
+                    Input::Command(None) => continue,
+                    Input::Idle => {
+                        if ping_sent.is_some() {
+                            return Err(WebSocketError::PongTimeout(self.heartbeat.pong_timeout));
+                        }
+                        write.send(Message::Text("ping".into())).await?;
+                        ping_sent = Some(Instant::now());
+                        continue;
+                    }
+                };
+
+                last_received = Instant::now();
+                ping_sent = None;
+                match message {
+                    Message::Text(text) if text == "pong" => {}
+                    Message::Text(text) => {
+                        let message = decode(text);
+                        subscriptions.handle_message(&message);
+                        tx.send(message).await.map_err(Box::new)?;
+                    }
+                    Message::Close(_) => break,
+                    _ => {}
+                }
+            }
+            Ok(())
+        }
+        .await;
+        (true, result)
+    }
+
+    /// Sends a `login` request and waits for its acknowledgement
+    ///
+    /// Messages received meanwhile, including the acknowledgement, are
+    /// forwarded to `tx`.
+    async fn login<W, R>(
+        credentials: &Credentials,
+        write: &mut W,
+        read: &mut R,
+        tx: &mpsc::Sender<WsMessage>,
+    ) -> Result<(), WebSocketError>
+    where
+        W: Sink<Message, Error = tungstenite::Error> + Unpin,
+        R: Stream<Item = Result<Message, tungstenite::Error>> + Unpin,
+    {
+        let request = credentials.login_request(&unix_timestamp());
+        write.send(Message::Text(request.to_string())).await?;
+
+        let acknowledgement = async {
+            while let Some(message) = read.next().await {
+                let Message::Text(text) = message? else {
+                    continue;
+                };
+                let message = decode(text);
+                let outcome = match &message {
+                    WsMessage::Event(WsEvent::Login { code, .. }) if code == "0" => Some(Ok(())),
+                    WsMessage::Event(
+                        WsEvent::Login { code, msg, .. } | WsEvent::Error { code, msg, .. },
+                    ) => Some(Err(WebSocketError::LoginFailed {
+                        code: code.clone(),
+                        msg: msg.clone(),
+                    })),
+                    _ => None,
+                };
+                tx.send(message).await.map_err(Box::new)?;
+                if let Some(outcome) = outcome {
+                    return outcome;
+                }
+            }
+            Err(WebSocketError::ConnectionClosed)
+        };
+        tokio::time::timeout(LOGIN_TIMEOUT, acknowledgement)
+            .await
+            .map_err(|_| WebSocketError::LoginTimeout(LOGIN_TIMEOUT))?
+    }
+}
+
+/// Decodes a text frame, keeping frames that fail to parse as [`WsMessage::Unknown`]
+///
+/// A single frame the client does not understand must not end the session.
+fn decode(text: String) -> WsMessage {
+    WsMessage::parse(&text).unwrap_or(WsMessage::Unknown(text))
+}
+
+/// What woke up the session loop
+enum Input {
+    Frame(Option<Result<Message, tungstenite::Error>>),
+    Command(Option<Command>),
+    Idle,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::testing::{accept, ws_server};
+    use crate::models::{CancelRequest, OrderRequest, OrderSide};
+
+    #[tokio::test]
+    async fn test_subscribe_reconnects_and_replays_subscriptions() {
+        let (listener, url) = ws_server().await;
+
+        let server = tokio::spawn(async move {
+            let mut subscriptions = Vec::new();
+            for connection in 0..2 {
+                let mut ws = accept(&listener).await;
+                let Some(Ok(Message::Text(text))) = ws.next().await else {
+                    panic!("expected a subscribe request");
+                };
+                subscriptions.push(text);
+                if connection == 0 {
+                    ws.close(None).await.unwrap();
+                    continue;
+                }
+                let push = r#"{"arg":{"channel":"books","instId":"BTC-USDT"},"action":"snapshot","data":[]}"#;
+                ws.send(Message::Text(push.into())).await.unwrap();
+                tokio::time::sleep(Duration::from_millis(100)).await;
+            }
+            subscriptions
+        });
+
+        let client = OKXWebSocketClient::new(&url).with_reconnect(ReconnectConfig {
+            initial_delay: Duration::from_millis(10),
+            max_attempts: Some(1),
+            ..Default::default()
         });
+        let (tx, mut rx) = mpsc::channel(16);
+        tokio::spawn(async move { client.subscribe_to_order_book("BTC-USDT", tx).await });
+
+        assert_eq!(
+            rx.recv().await.unwrap(),
+            WsMessage::Reconnected { attempts: 1 }
+        );
+        assert!(matches!(rx.recv().await.unwrap(), WsMessage::Push(_)));
 
-        write
-            .send(Message::Text(subscribe_message.to_string()))
-            .await?;
+        let subscriptions = server.await.unwrap();
+        assert_eq!(subscriptions[0], subscriptions[1]);
+    }
+
+    #[tokio::test]
+    async fn test_undecodable_frame_does_not_end_session() {
+        let (listener, url) = ws_server().await;
 
-        while let Some(message) = read.next().await {
-            match message? {
-                Message::Text(text) => {
-                    tx.send(text).await?;
+        tokio::spawn(async move {
+            let mut ws = accept(&listener).await;
+            ws.next().await.unwrap().unwrap();
+            let push =
+                r#"{"arg":{"channel":"books","instId":"BTC-USDT"},"action":"snapshot","data":[]}"#;
+            for frame in [r#"{"arg":42}"#, push] {
+                ws.send(Message::Text(frame.into())).await.unwrap();
+            }
+            while let Some(Ok(_)) = ws.next().await {}
+        });
+
+        let client = OKXWebSocketClient::new(&url);
+        let (tx, mut rx) = mpsc::channel(16);
+        tokio::spawn(async move { client.subscribe_to_order_book("BTC-USDT", tx).await });
+
+        assert_eq!(
+            rx.recv().await.unwrap(),
+            WsMessage::Unknown(r#"{"arg":42}"#.into())
+        );
+        assert!(matches!(rx.recv().await.unwrap(), WsMessage::Push(_)));
+    }
+
+    #[tokio::test]
+    async fn test_heartbeat_hides_pong_and_reconnects_on_timeout() {
+        let (listener, url) = ws_server().await;
+        let push =
+            r#"{"arg":{"channel":"books","instId":"BTC-USDT"},"action":"snapshot","data":[]}"#;
+
+        let server = tokio::spawn(async move {
+            for connection in 0..2 {
+                let mut ws = accept(&listener).await;
+                ws.next().await.unwrap().unwrap();
+                if connection == 1 {
+                    ws.send(Message::Text(push.into())).await.unwrap();
+                    tokio::time::sleep(Duration::from_millis(100)).await;
+                    continue;
                 }
-                Message::Close(frame) => {
-                    println!("WebSocket connection closed: {:?}", frame);
+                // Answer the first ping, then go silent so the client times out
+                let Some(Ok(Message::Text(ping))) = ws.next().await else {
+                    panic!("expected a ping");
+                };
+                assert_eq!(ping, "ping");
+                ws.send(Message::Text("pong".into())).await.unwrap();
+                ws.send(Message::Text(push.into())).await.unwrap();
+                while let Some(Ok(_)) = ws.next().await {}
+            }
+        });
+
+        let client = OKXWebSocketClient::new(&url)
+            .with_reconnect(ReconnectConfig {
+                initial_delay: Duration::from_millis(10),
+                max_attempts: Some(1),
+                ..Default::default()
+            })
+            .with_heartbeat(HeartbeatConfig {
+                interval: Duration::from_millis(50),
+                pong_timeout: Duration::from_millis(50),
+            });
+        let (tx, mut rx) = mpsc::channel(16);
+        tokio::spawn(async move { client.subscribe_to_order_book("BTC-USDT", tx).await });
+
+        assert!(matches!(rx.recv().await.unwrap(), WsMessage::Push(_)));
+        assert_eq!(
+            rx.recv().await.unwrap(),
+            WsMessage::Reconnected { attempts: 1 }
+        );
+        assert!(matches!(rx.recv().await.unwrap(), WsMessage::Push(_)));
+        server.await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_connection_subscribes_and_unsubscribes_at_runtime() {
+        let (listener, url) = ws_server().await;
+
+        let server = tokio::spawn(async move {
+            let mut ws = accept(&listener).await;
+            let mut requests = Vec::new();
+            while let Some(Ok(Message::Text(text))) = ws.next().await {
+                let request: serde_json::Value = serde_json::from_str(&text).unwrap();
+                for arg in request["args"].as_array().unwrap() {
+                    let ack = serde_json::json!({
+                        "id": request["id"],
+                        "event": request["op"],
+                        "arg": arg,
+                        "connId": "a4d3ae55",
+                    });
+                    ws.send(Message::Text(ack.to_string())).await.unwrap();
+                }
+                requests.push(request);
+                if requests.len() == 2 {
                     break;
                 }
-                _ => {}
             }
-        }
+            requests
+        });
+
+        let (tx, mut rx) = mpsc::channel(16);
+        let connection = OKXWebSocketClient::new(&url).connect(tx);
+        let args = vec![
+            ChannelArg::instrument("books", "BTC-USDT"),
+            ChannelArg::instrument("tickers", "ETH-USDT"),
+        ];
+        connection.subscribe(args.clone()).await.unwrap();
+        connection.unsubscribe(args[..1].to_vec()).await.unwrap();
+
+        let requests = server.await.unwrap();
+        assert_eq!(requests[0]["op"], "subscribe");
+        assert_eq!(requests[0]["args"].as_array().unwrap().len(), 2);
+        assert_eq!(requests[1]["op"], "unsubscribe");
+        assert_ne!(requests[0]["id"], requests[1]["id"]);
+        assert!(matches!(
+            rx.recv().await.unwrap(),
+            WsMessage::Event(WsEvent::Subscribe { .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_login_precedes_subscriptions_and_rejection_is_fatal() {
+        let (listener, url) = ws_server().await;
+
+        let server = tokio::spawn(async move {
+            let mut ops = Vec::new();
+            for code in ["0", "60009"] {
+                let mut ws = accept(&listener).await;
+                let Some(Ok(Message::Text(text))) = ws.next().await else {
+                    panic!("expected a login request");
+                };
+                let request: serde_json::Value = serde_json::from_str(&text).unwrap();
+                ops.push(request["op"].clone());
+                let response = if code == "0" {
+                    r#"{"event":"login","code":"0","msg":"","connId":"a4d3ae55"}"#
+                } else {
+                    r#"{"event":"error","code":"60009","msg":"Login failed.","connId":"a4d3ae55"}"#
+                };
+                ws.send(Message::Text(response.into())).await.unwrap();
+                if code == "0" {
+                    let Some(Ok(Message::Text(text))) = ws.next().await else {
+                        panic!("expected a subscribe request");
+                    };
+                    let request: serde_json::Value = serde_json::from_str(&text).unwrap();
+                    ops.push(request["op"].clone());
+                    ws.close(None).await.unwrap();
+                }
+            }
+            ops
+        });
+
+        let credentials = Credentials::new("key", "secret", "passphrase");
+        let client = OKXWebSocketClient::new(&url)
+            .with_credentials(credentials)
+            .with_reconnect(ReconnectConfig {
+                initial_delay: Duration::from_millis(10),
+                ..Default::default()
+            });
+        let (tx, mut rx) = mpsc::channel(16);
+        let args = vec![ChannelArg::new("orders")];
+        let result = client.subscribe(args, tx).await;
+
+        assert!(matches!(
+            result,
+            Err(WebSocketError::LoginFailed { code, .. }) if code == "60009"
+        ));
+        assert!(matches!(
+            rx.recv().await.unwrap(),
+            WsMessage::Event(WsEvent::Login { .. })
+        ));
+        assert_eq!(server.await.unwrap(), ["login", "subscribe", "login"]);
+    }
+
+    #[tokio::test]
+    async fn test_order_operations_resolve_with_responses() {
+        let (listener, url) = ws_server().await;
+
+        tokio::spawn(async move {
+            let mut ws = accept(&listener).await;
+            while let Some(Ok(Message::Text(text))) = ws.next().await {
+                let request: serde_json::Value = serde_json::from_str(&text).unwrap();
+                if request["op"] != "batch-orders" {
+                    continue;
+                }
+                let data: Vec<_> = request["args"]
+                    .as_array()
+                    .unwrap()
+                    .iter()
+                    .map(|order| match order["clOrdId"].as_str() {
+                        Some("poor") => serde_json::json!({
+                            "clOrdId": "poor", "ordId": "", "sCode": "51008",
+                            "sMsg": "Order failed. Insufficient balance.", "ts": "1695190491421339"
+                        }),
+                        _ => serde_json::json!({
+                            "clOrdId": order["clOrdId"], "ordId": "12345689", "sCode": "0",
+                            "sMsg": "", "ts": "1695190491421339"
+                        }),
+                    })
+                    .collect();
+                let response = serde_json::json!({
+                    "id": request["id"], "op": "batch-orders", "code": "2", "msg": "", "data": data
+                });
+                ws.send(Message::Text(response.to_string())).await.unwrap();
+            }
+        });
+
+        let (tx, _rx) = mpsc::channel(16);
+        let connection = OKXWebSocketClient::new(&url)
+            .with_request_timeout(Duration::from_millis(100))
+            .connect(tx);
+        let order = |cl_ord_id: &str| {
+            OrderRequest::limit(
+                "BTC-USDT",
+                OrderSide::Buy,
+                "2.15".parse().unwrap(),
+                "2".parse().unwrap(),
+            )
+            .with_cl_ord_id(cl_ord_id)
+        };
+
+        let results = connection
+            .place_orders(vec![order("rich"), order("poor")])
+            .await
+            .unwrap();
+        assert_eq!(results[0].as_ref().unwrap().ord_id, "12345689");
+        assert!(matches!(
+            &results[1],
+            Err(WebSocketError::OrderRejected { s_code, .. }) if s_code == "51008"
+        ));
 
-        Ok(())
+        let cancel = CancelRequest::by_ord_id("BTC-USDT", "12345689");
+        assert!(matches!(
+            connection.cancel_order(cancel).await,
+            Err(WebSocketError::RequestTimeout(_))
+        ));
     }
 }
@@ -2,5 +2,5 @@ pub mod client;
 pub mod models;
 pub mod utils;
 
-pub use client::{OKXRestClient, OKXWebSocketClient};
+pub use client::{LiveOrderbook, OKXRestClient, OKXWebSocketClient};
 pub use models::Orderbook;
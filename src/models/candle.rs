@@ -0,0 +1,195 @@
+use super::Price;
+use rust_decimal::Decimal;
+use serde::de::{self, Deserializer};
+use serde::Deserialize;
+use std::fmt;
+use std::str::FromStr;
+
+/// Candlestick bar size
+///
+/// Bars of 6 hours and longer are aligned to Hong Kong time (UTC+8) unless
+/// the `Utc` variant is used.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Bar {
+    Second1,
+    Minute1,
+    Minute3,
+    Minute5,
+    Minute15,
+    Minute30,
+    Hour1,
+    Hour2,
+    Hour4,
+    Hour6,
+    Hour12,
+    Day1,
+    Day2,
+    Day3,
+    Week1,
+    Month1,
+    Month3,
+    Hour6Utc,
+    Hour12Utc,
+    Day1Utc,
+    Day2Utc,
+    Day3Utc,
+    Week1Utc,
+    Month1Utc,
+    Month3Utc,
+}
+
+impl Bar {
+    pub const ALL: [Bar; 25] = [
+        Bar::Second1,
+        Bar::Minute1,
+        Bar::Minute3,
+        Bar::Minute5,
+        Bar::Minute15,
+        Bar::Minute30,
+        Bar::Hour1,
+        Bar::Hour2,
+        Bar::Hour4,
+        Bar::Hour6,
+        Bar::Hour12,
+        Bar::Day1,
+        Bar::Day2,
+        Bar::Day3,
+        Bar::Week1,
+        Bar::Month1,
+        Bar::Month3,
+        Bar::Hour6Utc,
+        Bar::Hour12Utc,
+        Bar::Day1Utc,
+        Bar::Day2Utc,
+        Bar::Day3Utc,
+        Bar::Week1Utc,
+        Bar::Month1Utc,
+        Bar::Month3Utc,
+    ];
+
+    /// The `bar` parameter value OKX uses, e.g. `1m`, `1H` or `1Dutc`
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Bar::Second1 => "1s",
+            Bar::Minute1 => "1m",
+            Bar::Minute3 => "3m",
+            Bar::Minute5 => "5m",
+            Bar::Minute15 => "15m",
+            Bar::Minute30 => "30m",
+            Bar::Hour1 => "1H",
+            Bar::Hour2 => "2H",
+            Bar::Hour4 => "4H",
+            Bar::Hour6 => "6H",
+            Bar::Hour12 => "12H",
+            Bar::Day1 => "1D",
+            Bar::Day2 => "2D",
+            Bar::Day3 => "3D",
+            Bar::Week1 => "1W",
+            Bar::Month1 => "1M",
+            Bar::Month3 => "3M",
+            Bar::Hour6Utc => "6Hutc",
+            Bar::Hour12Utc => "12Hutc",
+            Bar::Day1Utc => "1Dutc",
+            Bar::Day2Utc => "2Dutc",
+            Bar::Day3Utc => "3Dutc",
+            Bar::Week1Utc => "1Wutc",
+            Bar::Month1Utc => "1Mutc",
+            Bar::Month3Utc => "3Mutc",
+        }
+    }
+}
+
+impl FromStr for Bar {
+    type Err = String;
+
+    /// Parses OKX's bar names, which are case sensitive (`1m` is a minute, `1M` a month)
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Bar::ALL
+            .into_iter()
+            .find(|bar| bar.as_str() == s)
+            .ok_or_else(|| format!("Unknown bar size: {}", s))
+    }
+}
+
+impl fmt::Display for Bar {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// One OHLCV candlestick
+///
+/// OKX sends candles as arrays of
+/// `[ts, o, h, l, c, vol, volCcy, volCcyQuote, confirm]`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Candle {
+    /// Opening time of the bar in milliseconds
+    pub ts: u64,
+    pub open: Price,
+    pub high: Price,
+    pub low: Price,
+    pub close: Price,
+    /// Volume in base currency (spot) or contracts (derivatives)
+    pub vol: Decimal,
+    /// Volume in quote currency (spot) or base currency (derivatives)
+    pub vol_ccy: Decimal,
+    /// Volume in quote currency
+    pub vol_ccy_quote: Decimal,
+    /// `false` while the bar is still open
+    pub confirmed: bool,
+}
+
+impl<'de> Deserialize<'de> for Candle {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let fields = Vec::<String>::deserialize(deserializer)?;
+        if fields.len() < 9 {
+            return Err(de::Error::invalid_length(fields.len(), &"9 candle fields"));
+        }
+        Ok(Candle {
+            ts: parse(&fields[0])?,
+            open: parse(&fields[1])?,
+            high: parse(&fields[2])?,
+            low: parse(&fields[3])?,
+            close: parse(&fields[4])?,
+            vol: parse(&fields[5])?,
+            vol_ccy: parse(&fields[6])?,
+            vol_ccy_quote: parse(&fields[7])?,
+            confirmed: fields[8] == "1",
+        })
+    }
+}
+
+/// Parses one string-encoded candle field
+fn parse<T, E>(value: &str) -> Result<T, E>
+where
+    T: FromStr,
+    T::Err: fmt::Display,
+    E: de::Error,
+{
+    value.parse().map_err(E::custom)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bar_names_and_candle_deserialize() {
+        for bar in Bar::ALL {
+            assert_eq!(bar.as_str().parse(), Ok(bar));
+        }
+        assert_eq!("1M".parse(), Ok(Bar::Month1));
+        assert_eq!("1m".parse(), Ok(Bar::Minute1));
+        assert!("1h".parse::<Bar>().is_err());
+
+        let candles: Vec<Candle> = serde_json::from_str(
+            r#"[["1597026383085","3.721","3.743","3.677","3.708","8422410","22698348.04828491","12698348.04828491","0"]]"#,
+        )
+        .unwrap();
+        assert_eq!(candles[0].ts, 1597026383085);
+        assert_eq!(candles[0].close, "3.708".parse().unwrap());
+        assert_eq!(candles[0].vol, Decimal::from(8422410));
+        assert!(!candles[0].confirmed);
+        assert!(serde_json::from_str::<Candle>(r#"["1597026383085","3.721"]"#).is_err());
+    }
+}
@@ -0,0 +1,28 @@
+use serde::de::{self, Deserialize, Deserializer};
+use std::fmt::Display;
+use std::str::FromStr;
+
+/// Parses a value OKX encodes as a JSON string, e.g. a millisecond timestamp
+pub(crate) fn from_str<'de, D, T>(deserializer: D) -> Result<T, D::Error>
+where
+    D: Deserializer<'de>,
+    T: FromStr,
+    T::Err: Display,
+{
+    String::deserialize(deserializer)?
+        .parse()
+        .map_err(de::Error::custom)
+}
+
+/// Parses an optional string field, treating `""` (OKX's "not set") as `None`
+pub(crate) fn empty_as_none<'de, D, T>(deserializer: D) -> Result<Option<T>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: FromStr,
+    T::Err: Display,
+{
+    match Option::<String>::deserialize(deserializer)?.as_deref() {
+        None | Some("") => Ok(None),
+        Some(value) => value.parse().map(Some).map_err(de::Error::custom),
+    }
+}
@@ -0,0 +1,126 @@
+use rust_decimal::Decimal;
+use serde::de::{self, Visitor};
+use std::fmt;
+use std::marker::PhantomData;
+use std::str::FromStr;
+
+/// Converts a finite `f64` using its shortest round-trip representation
+pub(crate) fn decimal_from_f64(value: f64) -> Result<Decimal, rust_decimal::Error> {
+    if !value.is_finite() {
+        return Err(rust_decimal::Error::ConversionTo("Decimal".into()));
+    }
+    Decimal::from_str(&value.to_string())
+}
+
+/// Accepts decimals encoded either as JSON strings (as sent by OKX) or numbers
+pub(crate) struct DecimalVisitor<T>(PhantomData<T>);
+
+impl<T> DecimalVisitor<T> {
+    pub(crate) fn new() -> Self {
+        DecimalVisitor(PhantomData)
+    }
+}
+
+impl<T: From<Decimal>> Visitor<'_> for DecimalVisitor<T> {
+    type Value = T;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a decimal as a string or number")
+    }
+
+    fn visit_str<E: de::Error>(self, v: &str) -> Result<T, E> {
+        Decimal::from_str(v).map(T::from).map_err(E::custom)
+    }
+
+    fn visit_f64<E: de::Error>(self, v: f64) -> Result<T, E> {
+        decimal_from_f64(v).map(T::from).map_err(E::custom)
+    }
+
+    fn visit_u64<E: de::Error>(self, v: u64) -> Result<T, E> {
+        Ok(T::from(Decimal::from(v)))
+    }
+
+    fn visit_i64<E: de::Error>(self, v: i64) -> Result<T, E> {
+        Ok(T::from(Decimal::from(v)))
+    }
+}
+
+/// Implements the shared API of a `Decimal` newtype
+///
+/// The type parses from and prints back to the exact OKX string, serializes
+/// as a JSON string, deserializes from a string or number, and converts to
+/// `f64` for analytics.
+macro_rules! decimal_newtype {
+    ($name:ident) => {
+        impl $name {
+            /// Creates a value from a decimal
+            pub fn new(value: rust_decimal::Decimal) -> Self {
+                $name(value)
+            }
+
+            /// Returns the underlying decimal value
+            pub fn as_decimal(&self) -> rust_decimal::Decimal {
+                self.0
+            }
+
+            /// Converts to `f64`, which may lose precision
+            pub fn to_f64(&self) -> f64 {
+                rust_decimal::prelude::ToPrimitive::to_f64(&self.0).unwrap_or(f64::NAN)
+            }
+        }
+
+        impl std::str::FromStr for $name {
+            type Err = rust_decimal::Error;
+
+            fn from_str(s: &str) -> Result<Self, Self::Err> {
+                Ok($name(s.parse()?))
+            }
+        }
+
+        impl From<rust_decimal::Decimal> for $name {
+            fn from(value: rust_decimal::Decimal) -> Self {
+                $name(value)
+            }
+        }
+
+        impl From<$name> for rust_decimal::Decimal {
+            fn from(value: $name) -> Self {
+                value.0
+            }
+        }
+
+        impl TryFrom<f64> for $name {
+            type Error = rust_decimal::Error;
+
+            fn try_from(value: f64) -> Result<Self, Self::Error> {
+                Ok($name($crate::models::decimal::decimal_from_f64(value)?))
+            }
+        }
+
+        impl From<$name> for f64 {
+            fn from(value: $name) -> Self {
+                value.to_f64()
+            }
+        }
+
+        impl std::fmt::Display for $name {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                std::fmt::Display::fmt(&self.0, f)
+            }
+        }
+
+        impl serde::Serialize for $name {
+            fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                serializer.collect_str(&self.0)
+            }
+        }
+
+        impl<'de> serde::Deserialize<'de> for $name {
+            fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                deserializer.deserialize_any($crate::models::decimal::DecimalVisitor::new())
+            }
+        }
+    };
+}
+
+pub(crate) use decimal_newtype;
@@ -0,0 +1,140 @@
+use super::{Price, Size};
+use serde::de::{self, IgnoredAny, SeqAccess, Visitor};
+use serde::ser::SerializeTuple;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt;
+
+/// A single order book level as published by OKX
+///
+/// OKX sends each level as `[price, size, liquidatedOrders, numOrders]`. The
+/// two-element `[price, size]` form is also accepted, with both counts zero.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Level {
+    /// Price of the level
+    pub price: Price,
+    /// Total size resting at the price
+    pub size: Size,
+    /// Number of liquidated orders at the price
+    pub liquidated_orders: u64,
+    /// Number of orders at the price
+    pub num_orders: u64,
+}
+
+impl Level {
+    /// Creates a level with full order statistics
+    pub fn new(price: Price, size: Size, liquidated_orders: u64, num_orders: u64) -> Self {
+        Level {
+            price,
+            size,
+            liquidated_orders,
+            num_orders,
+        }
+    }
+
+    /// Returns the `(price, size)` view of the level
+    pub fn as_pair(&self) -> (Price, Size) {
+        (self.price, self.size)
+    }
+}
+
+impl From<(Price, Size)> for Level {
+    fn from((price, size): (Price, Size)) -> Self {
+        Level::new(price, size, 0, 0)
+    }
+}
+
+impl From<Level> for (Price, Size) {
+    fn from(level: Level) -> Self {
+        level.as_pair()
+    }
+}
+
+impl Serialize for Level {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut tuple = serializer.serialize_tuple(4)?;
+        tuple.serialize_element(&self.price)?;
+        tuple.serialize_element(&self.size)?;
+        tuple.serialize_element(&self.liquidated_orders.to_string())?;
+        tuple.serialize_element(&self.num_orders.to_string())?;
+        tuple.end()
+    }
+}
+
+impl<'de> Deserialize<'de> for Level {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_seq(LevelVisitor)
+    }
+}
+
+struct LevelVisitor;
+
+impl<'de> Visitor<'de> for LevelVisitor {
+    type Value = Level;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("an order book level array")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Level, A::Error> {
+        let price = seq
+            .next_element()?
+            .ok_or_else(|| de::Error::invalid_length(0, &self))?;
+        let size = seq
+            .next_element()?
+            .ok_or_else(|| de::Error::invalid_length(1, &self))?;
+        let liquidated_orders = seq.next_element::<Count>()?.unwrap_or_default();
+        let num_orders = seq.next_element::<Count>()?.unwrap_or_default();
+        while seq.next_element::<IgnoredAny>()?.is_some() {}
+        Ok(Level::new(price, size, liquidated_orders.0, num_orders.0))
+    }
+}
+
+/// An order count encoded as a JSON string or number
+#[derive(Default)]
+struct Count(u64);
+
+impl<'de> Deserialize<'de> for Count {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_any(CountVisitor)
+    }
+}
+
+struct CountVisitor;
+
+impl Visitor<'_> for CountVisitor {
+    type Value = Count;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("an order count as a string or number")
+    }
+
+    fn visit_str<E: de::Error>(self, v: &str) -> Result<Count, E> {
+        v.parse().map(Count).map_err(E::custom)
+    }
+
+    fn visit_u64<E: de::Error>(self, v: u64) -> Result<Count, E> {
+        Ok(Count(v))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_level_deserialize_full_and_pair_forms() {
+        let levels: Vec<Level> =
+            serde_json::from_str(r#"[["41006.8","0.6","2","7"],[41006.9,0.1]]"#).unwrap();
+        assert_eq!(levels[0].liquidated_orders, 2);
+        assert_eq!(levels[0].num_orders, 7);
+        assert_eq!(
+            levels[1].as_pair(),
+            ("41006.9".parse().unwrap(), "0.1".parse().unwrap())
+        );
+        assert_eq!(levels[1].num_orders, 0);
+        assert_eq!(
+            serde_json::to_string(&levels[0]).unwrap(),
+            r#"["41006.8","0.6","2","7"]"#
+        );
+    }
+}
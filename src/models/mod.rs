@@ -1,3 +1,29 @@
+pub mod candle;
+pub(crate) mod de;
+pub(crate) mod decimal;
+pub mod level;
+pub mod order;
 pub mod orderbook;
+pub mod portfolio;
+pub mod price;
+pub mod response;
+pub mod size;
+pub mod ticker;
+pub mod trade;
+pub mod trading;
+pub mod ws_message;
 
-pub use orderbook::Orderbook;
+pub use candle::{Bar, Candle};
+pub use level::Level;
+pub use order::{Order, OrderSide, OrderState, OrderTracker};
+pub use orderbook::{BookSide, Orderbook, Side};
+pub use portfolio::{Balance, Portfolio, Position};
+pub use price::Price;
+pub use response::{ApiErrorKind, ApiResponse};
+pub use size::Size;
+pub use ticker::Ticker;
+pub use trade::{Trade, TradeCursor};
+pub use trading::{
+    AmendRequest, CancelRequest, MassCancelAck, MassCancelRequest, OrderAck, OrderRequest,
+};
+pub use ws_message::{ChannelArg, WsEvent, WsMessage, WsPush, WsResponse};
@@ -0,0 +1,238 @@
+use super::de::{empty_as_none, from_str};
+use super::ws_message::WsPush;
+use super::{Price, Size};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Direction of an order or trade
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OrderSide {
+    Buy,
+    Sell,
+}
+
+/// Lifecycle state of an order
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OrderState {
+    Live,
+    PartiallyFilled,
+    Filled,
+    Canceled,
+    /// Canceled by market maker protection
+    MmpCanceled,
+    /// A state this crate does not know about yet
+    #[serde(other)]
+    Unknown,
+}
+
+impl OrderState {
+    /// Returns `true` if the order can no longer change
+    pub fn is_terminal(&self) -> bool {
+        matches!(
+            self,
+            OrderState::Filled | OrderState::Canceled | OrderState::MmpCanceled
+        )
+    }
+}
+
+/// An order as pushed on the private `orders` channel
+///
+/// Fields OKX sends as empty strings when not applicable, such as the price
+/// of a market order or the fill details of an order without a new fill,
+/// are `None`.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Order {
+    pub inst_type: String,
+    pub inst_id: String,
+    pub ord_id: String,
+    /// Client-supplied order ID
+    #[serde(default, deserialize_with = "empty_as_none")]
+    pub cl_ord_id: Option<String>,
+    #[serde(default, deserialize_with = "empty_as_none")]
+    pub tag: Option<String>,
+    /// Limit price, `None` for market orders
+    #[serde(default, deserialize_with = "empty_as_none")]
+    pub px: Option<Price>,
+    pub sz: Size,
+    /// `market`, `limit`, `post_only`, `fok`, `ioc`, ...
+    pub ord_type: String,
+    pub side: OrderSide,
+    #[serde(default, deserialize_with = "empty_as_none")]
+    pub pos_side: Option<String>,
+    pub td_mode: String,
+    /// Price of the latest fill
+    #[serde(default, deserialize_with = "empty_as_none")]
+    pub fill_px: Option<Price>,
+    /// Size of the latest fill
+    #[serde(default, deserialize_with = "empty_as_none")]
+    pub fill_sz: Option<Size>,
+    /// Trade ID of the latest fill
+    #[serde(default, deserialize_with = "empty_as_none")]
+    pub trade_id: Option<String>,
+    /// Cumulative filled size
+    pub acc_fill_sz: Size,
+    /// Average filled price
+    #[serde(default, deserialize_with = "empty_as_none")]
+    pub avg_px: Option<Price>,
+    pub state: OrderState,
+    /// Accumulated fee; negative values are charged, positive values rebates
+    #[serde(default, deserialize_with = "empty_as_none")]
+    pub fee: Option<Decimal>,
+    #[serde(default, deserialize_with = "empty_as_none")]
+    pub fee_ccy: Option<String>,
+    /// Creation time in milliseconds
+    #[serde(deserialize_with = "from_str")]
+    pub c_time: u64,
+    /// Last update time in milliseconds
+    #[serde(deserialize_with = "from_str")]
+    pub u_time: u64,
+}
+
+/// Local view of orders built from `orders` channel pushes
+///
+/// Orders are keyed by `ordId` and can also be looked up by `clOrdId`.
+/// A push older than the stored state (by `uTime`), or one that would move
+/// an order out of a terminal state, is ignored so that replayed or
+/// out-of-order pushes cannot regress an order.
+#[derive(Debug, Clone, Default)]
+pub struct OrderTracker {
+    orders: HashMap<String, Order>,
+    client_ids: HashMap<String, String>,
+}
+
+impl OrderTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Records `order`, returning `true` if the stored state changed
+    pub fn apply(&mut self, order: Order) -> bool {
+        if let Some(current) = self.orders.get(&order.ord_id) {
+            let regresses = current.state.is_terminal() && !order.state.is_terminal();
+            if order.u_time < current.u_time || regresses {
+                return false;
+            }
+        }
+        if let Some(cl_ord_id) = &order.cl_ord_id {
+            self.client_ids
+                .insert(cl_ord_id.clone(), order.ord_id.clone());
+        }
+        self.orders.insert(order.ord_id.clone(), order);
+        true
+    }
+
+    /// Applies every order of an `orders` push, returning those that changed
+    pub fn apply_push(&mut self, push: &WsPush) -> Result<Vec<Order>, serde_json::Error> {
+        let orders: Vec<Order> = push.decode()?;
+        Ok(orders
+            .into_iter()
+            .filter(|order| self.apply(order.clone()))
+            .collect())
+    }
+
+    /// Looks up an order by exchange order ID
+    pub fn get(&self, ord_id: &str) -> Option<&Order> {
+        self.orders.get(ord_id)
+    }
+
+    /// Looks up an order by client order ID
+    pub fn get_by_client_id(&self, cl_ord_id: &str) -> Option<&Order> {
+        self.client_ids
+            .get(cl_ord_id)
+            .and_then(|ord_id| self.orders.get(ord_id))
+    }
+
+    /// Orders that are still live or partially filled
+    pub fn open_orders(&self) -> impl Iterator<Item = &Order> {
+        self.orders
+            .values()
+            .filter(|order| !order.state.is_terminal())
+    }
+
+    /// Forgets orders that reached a terminal state
+    pub fn remove_terminal(&mut self) {
+        self.orders.retain(|_, order| !order.state.is_terminal());
+        let orders = &self.orders;
+        self.client_ids
+            .retain(|_, ord_id| orders.contains_key(ord_id));
+    }
+
+    pub fn len(&self) -> usize {
+        self.orders.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.orders.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::ws_message::parse_push;
+
+    const PUSH: &str = r#"{"arg":{"channel":"orders","instType":"SPOT","uid":"614488474791936"},"data":[{"accFillSz":"0","avgPx":"","cTime":"1654084334977","category":"normal","ccy":"","clOrdId":"b15","code":"0","fee":"0","feeCcy":"USDT","fillPx":"","fillSz":"0","fillTime":"","instId":"BTC-USDT","instType":"SPOT","lever":"0","msg":"","ordId":"452197707845865472","ordType":"limit","pnl":"0","posSide":"","px":"31527.1","side":"sell","source":"","state":"live","sz":"0.002","tag":"","tdMode":"cash","tradeId":"","uTime":"1654084334977"}]}"#;
+
+    fn push(state: &str, acc_fill_sz: &str, u_time: &str) -> WsPush {
+        let text = PUSH
+            .replace(r#""state":"live""#, &format!(r#""state":"{state}""#))
+            .replace(
+                r#""accFillSz":"0""#,
+                &format!(r#""accFillSz":"{acc_fill_sz}""#),
+            )
+            .replace(
+                r#""uTime":"1654084334977""#,
+                &format!(r#""uTime":"{u_time}""#),
+            );
+        parse_push(&text)
+    }
+
+    #[test]
+    fn test_decode_order() {
+        let order: Vec<Order> = push("live", "0", "1654084334977").decode().unwrap();
+        let order = &order[0];
+        assert_eq!(order.cl_ord_id.as_deref(), Some("b15"));
+        assert_eq!(order.px, Some("31527.1".parse().unwrap()));
+        assert_eq!(order.side, OrderSide::Sell);
+        assert_eq!(order.state, OrderState::Live);
+        assert_eq!(order.avg_px, None);
+        assert_eq!(order.pos_side, None);
+        assert_eq!(order.u_time, 1654084334977);
+    }
+
+    #[test]
+    fn test_tracker_follows_lifecycle_and_ignores_stale_pushes() {
+        let mut tracker = OrderTracker::new();
+        assert_eq!(
+            tracker.apply_push(&push("live", "0", "100")).unwrap().len(),
+            1
+        );
+        tracker
+            .apply_push(&push("partially_filled", "0.001", "200"))
+            .unwrap();
+        assert_eq!(tracker.open_orders().count(), 1);
+
+        assert!(tracker
+            .apply_push(&push("live", "0", "150"))
+            .unwrap()
+            .is_empty());
+        tracker.apply_push(&push("filled", "0.002", "300")).unwrap();
+        assert!(tracker
+            .apply_push(&push("partially_filled", "0.001", "300"))
+            .unwrap()
+            .is_empty());
+
+        let order = tracker.get_by_client_id("b15").unwrap();
+        assert_eq!(order.state, OrderState::Filled);
+        assert_eq!(order.acc_fill_sz, "0.002".parse().unwrap());
+        assert_eq!(tracker.open_orders().count(), 0);
+
+        tracker.remove_terminal();
+        assert!(tracker.is_empty());
+        assert!(tracker.get_by_client_id("b15").is_none());
+    }
+}
@@ -1,5 +1,6 @@
+use super::{Level, Price, Size};
 use serde::{Deserialize, Serialize};
-use std::cmp::Ordering;
+use std::collections::{btree_map, BTreeMap};
 use thiserror::Error;
 
 #[derive(Error, Debug)]
@@ -8,44 +9,300 @@ pub enum OrderbookError {
     JsonParseError(#[from] serde_json::Error),
     #[error("Empty response data")]
     EmptyData,
-    #[error("Invalid price data: NaN or infinite values")]
-    InvalidPriceData,
+    #[error("Negative level size")]
+    NegativeSize,
     #[error("Invalid timestamp format: {0}")]
     InvalidTimestamp(#[from] std::num::ParseIntError),
+    #[error("Checksum mismatch: expected {expected}, computed {computed}")]
+    ChecksumMismatch { expected: i32, computed: i32 },
+    #[error("Sequence gap: book is at seqId {expected}, update has prevSeqId {received}")]
+    SequenceGap { expected: i64, received: i64 },
 }
 
+/// Number of levels per side covered by the OKX book checksum
+pub const CHECKSUM_DEPTH: usize = 25;
+
+/// Which side of the book a set of levels belongs to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Side {
+    /// Sell side, best price is the lowest
+    Ask,
+    /// Buy side, best price is the highest
+    Bid,
+}
+
+/// One side of an order book, keyed by exact price
+///
+/// Upserts and deletes are O(log n) and the best level is read from the
+/// appropriate end of the map without sorting.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BookSide {
+    side: Side,
+    levels: BTreeMap<Price, Level>,
+}
+
+impl BookSide {
+    /// Creates an empty side
+    pub fn new(side: Side) -> Self {
+        BookSide {
+            side,
+            levels: BTreeMap::new(),
+        }
+    }
+
+    /// Creates a side from levels or `(price, size)` pairs in any order
+    ///
+    /// Later levels with the same price overwrite earlier ones and zero sizes
+    /// are skipped.
+    pub fn from_levels<I, L>(side: Side, levels: I) -> Result<Self, OrderbookError>
+    where
+        I: IntoIterator<Item = L>,
+        L: Into<Level>,
+    {
+        let mut book_side = BookSide::new(side);
+        for level in levels {
+            book_side.apply_level(level.into())?;
+        }
+        Ok(book_side)
+    }
+
+    /// Returns which side of the book this is
+    pub fn side(&self) -> Side {
+        self.side
+    }
+
+    /// Returns the number of price levels
+    pub fn len(&self) -> usize {
+        self.levels.len()
+    }
+
+    /// Returns `true` if the side has no levels
+    pub fn is_empty(&self) -> bool {
+        self.levels.is_empty()
+    }
+
+    /// Returns the best level: lowest ask or highest bid
+    pub fn best(&self) -> Option<(Price, Size)> {
+        self.iter().next()
+    }
+
+    /// Returns the best level with its order counts
+    pub fn best_level(&self) -> Option<&Level> {
+        self.levels().next()
+    }
+
+    /// Returns the size resting at `price`, if any
+    pub fn get(&self, price: &Price) -> Option<Size> {
+        self.levels.get(price).map(|level| level.size)
+    }
+
+    /// Returns the full level at `price`, if any
+    pub fn level(&self, price: &Price) -> Option<&Level> {
+        self.levels.get(price)
+    }
+
+    /// Sets the size at `price`, removing the level when `size` is zero
+    ///
+    /// Order counts of the level are reset to zero.
+    pub fn apply(&mut self, price: Price, size: Size) -> Result<(), OrderbookError> {
+        self.apply_level(Level::from((price, size)))
+    }
+
+    /// Replaces the level at its price, removing it when its size is zero
+    pub fn apply_level(&mut self, level: Level) -> Result<(), OrderbookError> {
+        if level.size.is_sign_negative() {
+            return Err(OrderbookError::NegativeSize);
+        }
+        if level.size.is_zero() {
+            self.levels.remove(&level.price);
+        } else {
+            self.levels.insert(level.price, level);
+        }
+        Ok(())
+    }
+
+    /// Removes the level at `price`, returning it
+    pub fn remove(&mut self, price: &Price) -> Option<Level> {
+        self.levels.remove(price)
+    }
+
+    /// Iterates `(price, size)` from best to worst: ascending asks, descending bids
+    pub fn iter(&self) -> Levels<'_> {
+        Levels {
+            inner: self.levels(),
+        }
+    }
+
+    /// Iterates full levels from best to worst
+    pub fn levels(&self) -> FullLevels<'_> {
+        FullLevels {
+            inner: self.levels.values(),
+            descending: self.side == Side::Bid,
+        }
+    }
+}
+
+impl<'a> IntoIterator for &'a BookSide {
+    type Item = (Price, Size);
+    type IntoIter = Levels<'a>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// Iterator over the full levels of a [`BookSide`] from best to worst price
+pub struct FullLevels<'a> {
+    inner: btree_map::Values<'a, Price, Level>,
+    descending: bool,
+}
+
+impl<'a> Iterator for FullLevels<'a> {
+    type Item = &'a Level;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.descending {
+            self.inner.next_back()
+        } else {
+            self.inner.next()
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl DoubleEndedIterator for FullLevels<'_> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.descending {
+            self.inner.next()
+        } else {
+            self.inner.next_back()
+        }
+    }
+}
+
+impl ExactSizeIterator for FullLevels<'_> {}
+
+/// Iterator over the `(price, size)` pairs of a [`BookSide`] from best to worst price
+pub struct Levels<'a> {
+    inner: FullLevels<'a>,
+}
+
+impl Iterator for Levels<'_> {
+    type Item = (Price, Size);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next().map(Level::as_pair)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl DoubleEndedIterator for Levels<'_> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.inner.next_back().map(Level::as_pair)
+    }
+}
+
+impl ExactSizeIterator for Levels<'_> {}
+
 /// Represents an order book with asks and bids
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(into = "OrderbookLevels", try_from = "OrderbookLevels")]
 pub struct Orderbook {
-    /// Ask orders (sell orders), sorted in ascending order by price
-    pub asks: Vec<(f64, f64)>,
-    /// Bid orders (buy orders), sorted in descending order by price
-    pub bids: Vec<(f64, f64)>,
+    /// Ask orders (sell orders), iterated in ascending order by price
+    pub asks: BookSide,
+    /// Bid orders (buy orders), iterated in descending order by price
+    pub bids: BookSide,
     /// Timestamp of the order book data
     pub ts: u64,
+    /// OKX `seqId` of the last applied snapshot or update, if known
+    pub seq_id: Option<i64>,
 }
 
+/// Serialized form of an [`Orderbook`], with each side as an ordered list
 #[derive(Debug, Serialize, Deserialize)]
+struct OrderbookLevels {
+    asks: Vec<Level>,
+    bids: Vec<Level>,
+    ts: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    seq_id: Option<i64>,
+}
+
+impl From<Orderbook> for OrderbookLevels {
+    fn from(orderbook: Orderbook) -> Self {
+        OrderbookLevels {
+            asks: orderbook.asks.levels().copied().collect(),
+            bids: orderbook.bids.levels().copied().collect(),
+            ts: orderbook.ts,
+            seq_id: orderbook.seq_id,
+        }
+    }
+}
+
+impl TryFrom<OrderbookLevels> for Orderbook {
+    type Error = OrderbookError;
+
+    fn try_from(levels: OrderbookLevels) -> Result<Self, Self::Error> {
+        let mut orderbook = Orderbook::new(levels.asks, levels.bids, levels.ts)?;
+        orderbook.seq_id = levels.seq_id;
+        Ok(orderbook)
+    }
+}
+
+#[derive(Debug, Deserialize)]
 struct OrderbookSnapshotResponse {
-    code: String,
-    msg: String,
     data: Vec<RawOrderbookData>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
-struct RawOrderbookData {
-    asks: Vec<(f64, f64)>,
-    bids: Vec<(f64, f64)>,
+/// A full book as carried by REST responses and WebSocket `snapshot` pushes
+#[derive(Debug, Deserialize)]
+pub(crate) struct RawOrderbookData {
+    asks: Vec<Level>,
+    bids: Vec<Level>,
     ts: String,
+    checksum: Option<i32>,
+    #[serde(rename = "seqId")]
+    seq_id: Option<i64>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
-struct OrderbookUpdate {
-    asks: Vec<(f64, f64)>,
-    bids: Vec<(f64, f64)>,
+/// Incremental levels as carried by WebSocket `update` pushes
+#[derive(Debug, Deserialize)]
+pub(crate) struct OrderbookUpdate {
+    asks: Vec<Level>,
+    bids: Vec<Level>,
+    ts: Option<String>,
+    /// Signed CRC32 of the top levels after applying this update
+    checksum: Option<i32>,
+    #[serde(rename = "seqId")]
+    seq_id: Option<i64>,
+    #[serde(rename = "prevSeqId")]
+    prev_seq_id: Option<i64>,
 }
 
 impl Orderbook {
+    /// Creates an Orderbook from unsorted ask and bid levels
+    ///
+    /// Accepts either full [`Level`]s or `(price, size)` pairs.
+    pub fn new<L: Into<Level>>(
+        asks: Vec<L>,
+        bids: Vec<L>,
+        ts: u64,
+    ) -> Result<Self, OrderbookError> {
+        Ok(Orderbook {
+            asks: BookSide::from_levels(Side::Ask, asks)?,
+            bids: BookSide::from_levels(Side::Bid, bids)?,
+            ts,
+            seq_id: None,
+        })
+    }
+
     /// Creates a new Orderbook from a JSON snapshot string
     pub fn from_snapshot(data: &str) -> Result<Self, OrderbookError> {
         let response: OrderbookSnapshotResponse = serde_json::from_str(data)?;
@@ -55,60 +312,152 @@ impl Orderbook {
             .next()
             .ok_or(OrderbookError::EmptyData)?;
 
+        Orderbook::from_raw(raw_data)
+    }
+
+    /// Builds a book from snapshot data, verifying its checksum when present
+    pub(crate) fn from_raw(raw_data: RawOrderbookData) -> Result<Self, OrderbookError> {
         let ts = raw_data.ts.parse::<u64>()?;
 
-        let mut orderbook = Orderbook {
-            asks: raw_data.asks,
-            bids: raw_data.bids,
+        let orderbook = Orderbook {
+            asks: BookSide::from_levels(Side::Ask, raw_data.asks)?,
+            bids: BookSide::from_levels(Side::Bid, raw_data.bids)?,
             ts,
+            seq_id: raw_data.seq_id,
         };
-
-        orderbook.sort_order_book()?;
+        if let Some(expected) = raw_data.checksum {
+            orderbook.verify_checksum(expected)?;
+        }
         Ok(orderbook)
     }
 
     /// Applies an incremental update to the order book
+    ///
+    /// Each level replaces the size at its price, a size of zero removes the
+    /// level, and unseen prices are inserted at their sorted position. When
+    /// the update carries a `checksum`, the resulting book is verified against
+    /// it and [`OrderbookError::ChecksumMismatch`] is returned on divergence,
+    /// in which case the book should be rebuilt from a fresh snapshot.
+    ///
+    /// When both the book and the update carry sequence numbers, the update's
+    /// `prevSeqId` must equal the book's `seqId`, otherwise
+    /// [`OrderbookError::SequenceGap`] is returned and nothing is applied. An
+    /// update whose `prevSeqId` equals its `seqId` is a heartbeat and leaves
+    /// the levels untouched, though its `checksum` is still verified.
     pub fn apply_update(&mut self, update: &str) -> Result<(), OrderbookError> {
-        let update: OrderbookUpdate = serde_json::from_str(update)?;
-        self.asks.extend(update.asks);
-        self.bids.extend(update.bids);
-        self.sort_order_book()?;
-        Ok(())
+        self.apply(serde_json::from_str(update)?)
     }
 
-    /// Sorts the order book: asks in ascending order, bids in descending order
-    fn sort_order_book(&mut self) -> Result<(), OrderbookError> {
-        // Validate that all prices are valid (not NaN or infinite)
-        for (price, _) in self.asks.iter().chain(self.bids.iter()) {
-            if !price.is_finite() {
-                return Err(OrderbookError::InvalidPriceData);
+    /// Applies already decoded update data, see [`Orderbook::apply_update`]
+    pub(crate) fn apply(&mut self, update: OrderbookUpdate) -> Result<(), OrderbookError> {
+        let mut entries = update.asks.iter().chain(update.bids.iter());
+        if entries.any(|level| level.size.is_sign_negative()) {
+            return Err(OrderbookError::NegativeSize);
+        }
+        let ts = update.ts.as_deref().map(str::parse::<u64>).transpose()?;
+
+        if let (Some(current), Some(prev_seq_id)) = (self.seq_id, update.prev_seq_id) {
+            if prev_seq_id != current {
+                return Err(OrderbookError::SequenceGap {
+                    expected: current,
+                    received: prev_seq_id,
+                });
+            }
+        }
+        if let Some(ts) = ts {
+            self.ts = ts;
+        }
+        // Heartbeats carry no levels but still a checksum of the current book
+        let heartbeat = update.seq_id.is_some() && update.seq_id == update.prev_seq_id;
+        if !heartbeat {
+            if update.seq_id.is_some() {
+                self.seq_id = update.seq_id;
+            }
+            for level in update.asks {
+                self.asks.apply_level(level)?;
+            }
+            for level in update.bids {
+                self.bids.apply_level(level)?;
             }
         }
 
-        // Sort asks in ascending order by price
-        self.asks
-            .sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap_or(Ordering::Equal));
+        match update.checksum {
+            Some(expected) => self.verify_checksum(expected),
+            None => Ok(()),
+        }
+    }
 
-        // Sort bids in descending order by price
-        self.bids
-            .sort_by(|a, b| b.0.partial_cmp(&a.0).unwrap_or(Ordering::Equal));
+    /// Computes the OKX checksum over the top 25 levels of each side
+    ///
+    /// Levels are interleaved as `bidPx:bidSz:askPx:askSz` using their exact
+    /// received text, joined with `:`, and hashed with CRC32 into a signed
+    /// 32-bit integer.
+    pub fn checksum(&self) -> i32 {
+        let mut bids = self.bids.iter().take(CHECKSUM_DEPTH);
+        let mut asks = self.asks.iter().take(CHECKSUM_DEPTH);
+        let mut parts = Vec::with_capacity(CHECKSUM_DEPTH * 4);
+        for _ in 0..CHECKSUM_DEPTH {
+            if let Some((price, size)) = bids.next() {
+                parts.push(price.to_string());
+                parts.push(size.to_string());
+            }
+            if let Some((price, size)) = asks.next() {
+                parts.push(price.to_string());
+                parts.push(size.to_string());
+            }
+        }
+        crc32fast::hash(parts.join(":").as_bytes()) as i32
+    }
 
+    /// Verifies the book against a checksum pushed by OKX
+    pub fn verify_checksum(&self, expected: i32) -> Result<(), OrderbookError> {
+        let computed = self.checksum();
+        if computed != expected {
+            return Err(OrderbookError::ChecksumMismatch { expected, computed });
+        }
         Ok(())
     }
+
+    /// Returns the lowest ask level
+    pub fn best_ask(&self) -> Option<(Price, Size)> {
+        self.asks.best()
+    }
+
+    /// Returns the highest bid level
+    pub fn best_bid(&self) -> Option<(Price, Size)> {
+        self.bids.best()
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    fn px(price: &str) -> Price {
+        price.parse().unwrap()
+    }
+
+    fn sz(size: &str) -> Size {
+        size.parse().unwrap()
+    }
+
+    fn levels(side: &BookSide) -> Vec<(f64, f64)> {
+        side.iter()
+            .map(|(price, size)| (price.to_f64(), size.to_f64()))
+            .collect()
+    }
+
     #[test]
     fn test_orderbook_from_snapshot() {
         let data = r#"{"code":"0","msg":"","data":[{"asks":[[41006.8,0.60030921]],"bids":[[41006.3,0.30178210]],"ts":"1621447077008"}]}"#;
         let orderbook = Orderbook::from_snapshot(data).unwrap();
         assert_eq!(orderbook.asks.len(), 1);
         assert_eq!(orderbook.bids.len(), 1);
-        assert_eq!(orderbook.asks[0], (41006.8, 0.60030921));
-        assert_eq!(orderbook.bids[0], (41006.3, 0.30178210));
+        assert_eq!(
+            orderbook.best_ask(),
+            Some((px("41006.8"), sz("0.60030921")))
+        );
+        assert_eq!(orderbook.best_bid(), Some((px("41006.3"), sz("0.3017821"))));
         assert_eq!(orderbook.ts, 1621447077008);
     }
 
@@ -120,35 +469,160 @@ mod tests {
         orderbook.apply_update(update).unwrap();
         assert_eq!(orderbook.asks.len(), 2);
         assert_eq!(orderbook.bids.len(), 2);
-        assert_eq!(orderbook.asks[1], (41007.0, 0.20000000));
-        assert_eq!(orderbook.bids[1], (41005.0, 0.10000000));
+        assert_eq!(levels(&orderbook.asks)[1], (41007.0, 0.20000000));
+        assert_eq!(levels(&orderbook.bids)[1], (41005.0, 0.10000000));
     }
 
     #[test]
-    fn test_orderbook_sort_order_book() {
-        let mut orderbook = Orderbook {
-            asks: vec![(41007.0, 0.20000000), (41006.8, 0.60030921)],
-            bids: vec![(41005.0, 0.10000000), (41006.3, 0.30178210)],
-            ts: 1621447077008,
-        };
-        orderbook.sort_order_book().unwrap();
+    fn test_orderbook_apply_update_replaces_and_removes_levels() {
+        let data = r#"{"code":"0","msg":"","data":[{"asks":[[41006.8,0.6],[41007.0,0.2]],"bids":[[41006.3,0.3],[41005.0,0.1]],"ts":"1621447077008"}]}"#;
+        let mut orderbook = Orderbook::from_snapshot(data).unwrap();
+        let update = r#"{"asks":[[41006.8,0.0],[41007.0,0.5],[41006.9,0.4]],"bids":[["41005.0","0","0","0"],[41006.5,0.7],[41004.0,0]]}"#;
+        orderbook.apply_update(update).unwrap();
+        assert_eq!(
+            levels(&orderbook.asks),
+            vec![(41006.9, 0.4), (41007.0, 0.5)]
+        );
+        assert_eq!(
+            levels(&orderbook.bids),
+            vec![(41006.5, 0.7), (41006.3, 0.3)]
+        );
+    }
+
+    #[test]
+    fn test_orderbook_new_sorts_levels() {
+        let orderbook = Orderbook::new(
+            vec![
+                (px("41007.0"), sz("0.2")),
+                (px("41006.8"), sz("0.60030921")),
+            ],
+            vec![
+                (px("41005.0"), sz("0.1")),
+                (px("41006.3"), sz("0.30178210")),
+            ],
+            1621447077008,
+        )
+        .unwrap();
         assert_eq!(
-            orderbook.asks,
+            levels(&orderbook.asks),
             vec![(41006.8, 0.60030921), (41007.0, 0.20000000)]
         );
         assert_eq!(
-            orderbook.bids,
+            levels(&orderbook.bids),
             vec![(41006.3, 0.30178210), (41005.0, 0.10000000)]
         );
+        assert_eq!(
+            orderbook.bids.iter().next_back(),
+            Some((px("41005.0"), sz("0.1")))
+        );
     }
 
     #[test]
-    fn test_orderbook_invalid_price_data() {
-        let mut orderbook = Orderbook {
-            asks: vec![(f64::NAN, 0.20000000)],
-            bids: vec![(41006.3, 0.30178210)],
-            ts: 1621447077008,
-        };
-        assert!(orderbook.sort_order_book().is_err());
+    fn test_orderbook_rejects_negative_size() {
+        let result = Orderbook::new(
+            vec![(px("41007.0"), sz("-0.2"))],
+            vec![(px("41006.3"), sz("0.30178210"))],
+            1621447077008,
+        );
+        assert!(matches!(result, Err(OrderbookError::NegativeSize)));
+    }
+
+    #[test]
+    fn test_orderbook_serde_round_trip() {
+        let orderbook = Orderbook::new(
+            vec![(px("41006.8"), sz("0.6"))],
+            vec![(px("41006.3"), sz("0.3")), (px("41006.5"), sz("0.10"))],
+            1621447077008,
+        )
+        .unwrap();
+        let json = serde_json::to_string(&orderbook).unwrap();
+        assert_eq!(
+            json,
+            r#"{"asks":[["41006.8","0.6","0","0"]],"bids":[["41006.5","0.10","0","0"],["41006.3","0.3","0","0"]],"ts":1621447077008}"#
+        );
+        let decoded: Orderbook = serde_json::from_str(&json).unwrap();
+        assert_eq!(decoded.bids, orderbook.bids);
+    }
+
+    #[test]
+    fn test_orderbook_apply_update_tracks_sequence() {
+        let data = r#"{"code":"0","msg":"","data":[{"asks":[["3366.8","9","0","3"]],"bids":[["3366.1","7","0","3"]],"ts":"1597026383085","seqId":100}]}"#;
+        let mut orderbook = Orderbook::from_snapshot(data).unwrap();
+        assert_eq!(orderbook.seq_id, Some(100));
+
+        let update = r#"{"asks":[["3366.9","1","0","1"]],"bids":[],"ts":"1597026383090","prevSeqId":100,"seqId":105}"#;
+        orderbook.apply_update(update).unwrap();
+        assert_eq!(orderbook.seq_id, Some(105));
+        assert_eq!(orderbook.ts, 1597026383090);
+        assert_eq!(orderbook.asks.len(), 2);
+
+        let heartbeat = r#"{"asks":[],"bids":[],"ts":"1597026383095","prevSeqId":105,"seqId":105}"#;
+        orderbook.apply_update(heartbeat).unwrap();
+        assert_eq!(orderbook.seq_id, Some(105));
+        assert_eq!(orderbook.ts, 1597026383095);
+
+        let diverged = r#"{"asks":[],"bids":[],"checksum":12345,"prevSeqId":105,"seqId":105}"#;
+        assert!(matches!(
+            orderbook.apply_update(diverged),
+            Err(OrderbookError::ChecksumMismatch { .. })
+        ));
+    }
+
+    #[test]
+    fn test_orderbook_apply_update_sequence_gap() {
+        let data = r#"{"code":"0","msg":"","data":[{"asks":[["3366.8","9","0","3"]],"bids":[["3366.1","7","0","3"]],"ts":"1597026383085","seqId":100}]}"#;
+        let mut orderbook = Orderbook::from_snapshot(data).unwrap();
+
+        let update = r#"{"asks":[["3366.8","0","0","0"]],"bids":[],"prevSeqId":103,"seqId":110}"#;
+        assert!(matches!(
+            orderbook.apply_update(update),
+            Err(OrderbookError::SequenceGap {
+                expected: 100,
+                received: 103
+            })
+        ));
+        assert_eq!(orderbook.seq_id, Some(100));
+        assert_eq!(orderbook.asks.len(), 1);
+    }
+
+    #[test]
+    fn test_orderbook_keeps_order_counts() {
+        let data = r#"{"code":"0","msg":"","data":[{"asks":[["3366.8","9","1","3"]],"bids":[["3366.1","7","0","5"]],"ts":"1597026383085"}]}"#;
+        let mut orderbook = Orderbook::from_snapshot(data).unwrap();
+        assert_eq!(orderbook.bids.best_level().unwrap().num_orders, 5);
+
+        let update = r#"{"asks":[["3366.8","4","0","2"]],"bids":[]}"#;
+        orderbook.apply_update(update).unwrap();
+        let level = orderbook.asks.level(&px("3366.8")).unwrap();
+        assert_eq!(level.size, sz("4"));
+        assert_eq!((level.liquidated_orders, level.num_orders), (0, 2));
+        assert_eq!(orderbook.best_ask(), Some((px("3366.8"), sz("4"))));
+    }
+
+    #[test]
+    fn test_orderbook_checksum_uses_received_strings() {
+        let data = r#"{"code":"0","msg":"","data":[{"asks":[["3366.8","9","10","3"],["3368","8","3","4"]],"bids":[["3366.1","7","0","3"],["3366","6","3","4"],["3365.50","1.0","0","1"]],"ts":"1597026383085"}]}"#;
+        let orderbook = Orderbook::from_snapshot(data).unwrap();
+        let expected = crc32fast::hash(b"3366.1:7:3366.8:9:3366:6:3368:8:3365.50:1.0") as i32;
+        assert_eq!(orderbook.checksum(), expected);
+    }
+
+    #[test]
+    fn test_orderbook_apply_update_checksum_mismatch() {
+        let data = r#"{"code":"0","msg":"","data":[{"asks":[["3366.8","9","10","3"]],"bids":[["3366.1","7","0","3"]],"ts":"1597026383085"}]}"#;
+        let mut orderbook = Orderbook::from_snapshot(data).unwrap();
+
+        let valid = crc32fast::hash(b"3366.1:7:3366.8:5") as i32;
+        let update = format!(r#"{{"asks":[["3366.8","5","0","2"]],"bids":[],"checksum":{valid}}}"#);
+        orderbook.apply_update(&update).unwrap();
+
+        let update = r#"{"asks":[["3366.8","4","0","2"]],"bids":[],"checksum":12345}"#;
+        assert!(matches!(
+            orderbook.apply_update(update),
+            Err(OrderbookError::ChecksumMismatch {
+                expected: 12345,
+                ..
+            })
+        ));
     }
 }
@@ -0,0 +1,343 @@
+use super::de::{empty_as_none, from_str};
+use super::ws_message::WsPush;
+use super::Price;
+use rust_decimal::Decimal;
+use serde::Deserialize;
+use std::collections::HashMap;
+
+/// Balance of one currency, from the `account` channel
+///
+/// `balance_and_position` pushes only carry the cash balance; the other
+/// fields keep their last value from the `account` channel.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Balance {
+    pub ccy: String,
+    /// Equity of the currency
+    #[serde(default, deserialize_with = "empty_as_none")]
+    pub eq: Option<Decimal>,
+    #[serde(default, deserialize_with = "empty_as_none")]
+    pub cash_bal: Option<Decimal>,
+    /// Balance available for new orders
+    #[serde(default, deserialize_with = "empty_as_none")]
+    pub avail_bal: Option<Decimal>,
+    /// Equity available, in cross margin modes
+    #[serde(default, deserialize_with = "empty_as_none")]
+    pub avail_eq: Option<Decimal>,
+    #[serde(default, deserialize_with = "empty_as_none")]
+    pub frozen_bal: Option<Decimal>,
+    /// Unrealized profit and loss of all margin and derivatives positions
+    #[serde(default, deserialize_with = "empty_as_none")]
+    pub upl: Option<Decimal>,
+    /// Equity in USD
+    #[serde(default, deserialize_with = "empty_as_none")]
+    pub eq_usd: Option<Decimal>,
+    #[serde(deserialize_with = "from_str")]
+    pub u_time: u64,
+}
+
+/// An open position, from the `positions` channel
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Position {
+    pub pos_id: String,
+    pub inst_type: String,
+    pub inst_id: String,
+    /// `cross` or `isolated`
+    pub mgn_mode: String,
+    /// `long`, `short` or `net`
+    pub pos_side: String,
+    /// Quantity; negative for a short position in `net` mode
+    #[serde(deserialize_with = "from_str")]
+    pub pos: Decimal,
+    #[serde(default, deserialize_with = "empty_as_none")]
+    pub ccy: Option<String>,
+    #[serde(default, deserialize_with = "empty_as_none")]
+    pub avail_pos: Option<Decimal>,
+    #[serde(default, deserialize_with = "empty_as_none")]
+    pub avg_px: Option<Price>,
+    /// Unrealized profit and loss at the mark price
+    #[serde(default, deserialize_with = "empty_as_none")]
+    pub upl: Option<Decimal>,
+    #[serde(default, deserialize_with = "empty_as_none")]
+    pub upl_ratio: Option<Decimal>,
+    /// Estimated liquidation price
+    #[serde(default, deserialize_with = "empty_as_none")]
+    pub liq_px: Option<Price>,
+    #[serde(default, deserialize_with = "empty_as_none")]
+    pub mark_px: Option<Price>,
+    #[serde(default, deserialize_with = "empty_as_none")]
+    pub lever: Option<Decimal>,
+    #[serde(default, deserialize_with = "empty_as_none")]
+    pub margin: Option<Decimal>,
+    #[serde(default, deserialize_with = "empty_as_none")]
+    pub mgn_ratio: Option<Decimal>,
+    #[serde(deserialize_with = "from_str")]
+    pub u_time: u64,
+}
+
+/// `account` channel record
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct AccountData {
+    #[serde(deserialize_with = "from_str")]
+    u_time: u64,
+    #[serde(default, deserialize_with = "empty_as_none")]
+    total_eq: Option<Decimal>,
+    details: Vec<Balance>,
+}
+
+/// `balance_and_position` channel record
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct BalanceAndPositionData {
+    bal_data: Vec<CashBalance>,
+    pos_data: Vec<PositionData>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CashBalance {
+    ccy: String,
+    #[serde(deserialize_with = "from_str")]
+    cash_bal: Decimal,
+    #[serde(deserialize_with = "from_str")]
+    u_time: u64,
+}
+
+/// The subset of position fields carried by `balance_and_position`
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PositionData {
+    pos_id: String,
+    inst_type: String,
+    inst_id: String,
+    mgn_mode: String,
+    pos_side: String,
+    #[serde(deserialize_with = "from_str")]
+    pos: Decimal,
+    #[serde(default, deserialize_with = "empty_as_none")]
+    ccy: Option<String>,
+    #[serde(default, deserialize_with = "empty_as_none")]
+    avg_px: Option<Price>,
+    #[serde(deserialize_with = "from_str")]
+    u_time: u64,
+}
+
+impl From<PositionData> for Position {
+    fn from(data: PositionData) -> Self {
+        Position {
+            pos_id: data.pos_id,
+            inst_type: data.inst_type,
+            inst_id: data.inst_id,
+            mgn_mode: data.mgn_mode,
+            pos_side: data.pos_side,
+            pos: data.pos,
+            ccy: data.ccy,
+            avail_pos: None,
+            avg_px: data.avg_px,
+            upl: None,
+            upl_ratio: None,
+            liq_px: None,
+            mark_px: None,
+            lever: None,
+            margin: None,
+            mgn_ratio: None,
+            u_time: data.u_time,
+        }
+    }
+}
+
+/// Balances and positions kept up to date from private channel pushes
+///
+/// Feed it pushes from the `account`, `positions` and
+/// `balance_and_position` channels with [`Portfolio::apply_push`]. Updates
+/// older than the stored state (by `uTime`) are ignored, and positions are
+/// removed once their quantity drops to zero.
+#[derive(Debug, Clone, Default)]
+pub struct Portfolio {
+    total_eq: Option<Decimal>,
+    balances: HashMap<String, Balance>,
+    positions: HashMap<String, Position>,
+    u_time: u64,
+}
+
+impl Portfolio {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Applies a push, returning `false` if it is not from a portfolio channel
+    pub fn apply_push(&mut self, push: &WsPush) -> Result<bool, serde_json::Error> {
+        match push.arg.channel.as_str() {
+            "account" => {
+                for account in push.decode::<AccountData>()? {
+                    self.apply_account(account);
+                }
+            }
+            "positions" => {
+                for position in push.decode::<Position>()? {
+                    self.apply_position(position);
+                }
+            }
+            "balance_and_position" => {
+                for data in push.decode::<BalanceAndPositionData>()? {
+                    self.apply_balance_and_position(data);
+                }
+            }
+            _ => return Ok(false),
+        }
+        Ok(true)
+    }
+
+    fn apply_account(&mut self, account: AccountData) {
+        if account.u_time >= self.u_time {
+            self.u_time = account.u_time;
+            self.total_eq = account.total_eq;
+        }
+        for balance in account.details {
+            let stale = self
+                .balances
+                .get(&balance.ccy)
+                .is_some_and(|current| balance.u_time < current.u_time);
+            if !stale {
+                self.balances.insert(balance.ccy.clone(), balance);
+            }
+        }
+    }
+
+    fn apply_position(&mut self, position: Position) {
+        if let Some(current) = self.positions.get(&position.pos_id) {
+            if position.u_time < current.u_time {
+                return;
+            }
+        }
+        if position.pos.is_zero() {
+            self.positions.remove(&position.pos_id);
+        } else {
+            self.positions.insert(position.pos_id.clone(), position);
+        }
+    }
+
+    fn apply_balance_and_position(&mut self, data: BalanceAndPositionData) {
+        for cash in data.bal_data {
+            match self.balances.get_mut(&cash.ccy) {
+                Some(balance) if cash.u_time < balance.u_time => {}
+                Some(balance) => {
+                    balance.cash_bal = Some(cash.cash_bal);
+                    balance.u_time = cash.u_time;
+                }
+                None => {
+                    let balance = Balance {
+                        ccy: cash.ccy.clone(),
+                        eq: None,
+                        cash_bal: Some(cash.cash_bal),
+                        avail_bal: None,
+                        avail_eq: None,
+                        frozen_bal: None,
+                        upl: None,
+                        eq_usd: None,
+                        u_time: cash.u_time,
+                    };
+                    self.balances.insert(cash.ccy, balance);
+                }
+            }
+        }
+
+        for data in data.pos_data {
+            match self.positions.get_mut(&data.pos_id) {
+                Some(position) if data.u_time < position.u_time => {}
+                Some(position) if !data.pos.is_zero() => {
+                    position.pos = data.pos;
+                    position.avg_px = data.avg_px;
+                    position.u_time = data.u_time;
+                }
+                _ => self.apply_position(data.into()),
+            }
+        }
+    }
+
+    /// Total account equity in USD
+    pub fn total_eq(&self) -> Option<Decimal> {
+        self.total_eq
+    }
+
+    /// Balance of `ccy`
+    pub fn balance(&self, ccy: &str) -> Option<&Balance> {
+        self.balances.get(ccy)
+    }
+
+    pub fn balances(&self) -> impl Iterator<Item = &Balance> {
+        self.balances.values()
+    }
+
+    /// Position with the given `posId`
+    pub fn position(&self, pos_id: &str) -> Option<&Position> {
+        self.positions.get(pos_id)
+    }
+
+    /// Open positions on `inst_id`, one per margin mode and position side
+    pub fn positions_for<'a>(&'a self, inst_id: &'a str) -> impl Iterator<Item = &'a Position> {
+        self.positions
+            .values()
+            .filter(move |position| position.inst_id == inst_id)
+    }
+
+    pub fn positions(&self) -> impl Iterator<Item = &Position> {
+        self.positions.values()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::ws_message::parse_push;
+
+    fn dec(value: &str) -> Decimal {
+        value.parse().unwrap()
+    }
+
+    #[test]
+    fn test_account_and_balance_updates() {
+        let mut portfolio = Portfolio::new();
+        let account = r#"{"arg":{"channel":"account","uid":"44705892343619584"},"data":[{"adjEq":"","totalEq":"41624.32","uTime":"1614846244194","details":[{"availBal":"","availEq":"1","ccy":"BTC","cashBal":"1","eq":"1","eqUsd":"45078.3790756226851775","frozenBal":"0","upl":"0","uTime":"1614846244194"}]}]}"#;
+        assert!(portfolio.apply_push(&parse_push(account)).unwrap());
+        assert_eq!(portfolio.total_eq(), Some(dec("41624.32")));
+        let btc = portfolio.balance("BTC").unwrap();
+        assert_eq!(btc.avail_eq, Some(dec("1")));
+        assert_eq!(btc.avail_bal, None);
+
+        let update = r#"{"arg":{"channel":"balance_and_position","uid":"77982378738415879"},"data":[{"pTime":"1597026383085","eventType":"filled","balData":[{"ccy":"BTC","cashBal":"0.5","uTime":"1614846244195"}],"posData":[],"trades":[]}]}"#;
+        portfolio.apply_push(&parse_push(update)).unwrap();
+        let btc = portfolio.balance("BTC").unwrap();
+        assert_eq!(btc.cash_bal, Some(dec("0.5")));
+        assert_eq!(btc.eq, Some(dec("1")));
+
+        let tickers = r#"{"arg":{"channel":"tickers","instId":"BTC-USDT"},"data":[]}"#;
+        assert!(!portfolio.apply_push(&parse_push(tickers)).unwrap());
+    }
+
+    #[test]
+    fn test_positions_are_updated_and_closed() {
+        let mut portfolio = Portfolio::new();
+        let positions = r#"{"arg":{"channel":"positions","instType":"ANY","uid":"77982378738415879"},"data":[{"avgPx":"2566.31","availPos":"1","ccy":"ETH","instId":"ETH-USD-SWAP","instType":"SWAP","lever":"10","liqPx":"2352.8496681818233","markPx":"2353.849","mgnMode":"isolated","pos":"1","posId":"307173036051017730","posSide":"long","upl":"-0.0000033452492717","uplRatio":"-0.0008","uTime":"1614846244194"}]}"#;
+        portfolio.apply_push(&parse_push(positions)).unwrap();
+        let position = portfolio.positions_for("ETH-USD-SWAP").next().unwrap();
+        assert_eq!(position.liq_px, Some("2352.8496681818233".parse().unwrap()));
+        assert_eq!(position.pos, dec("1"));
+
+        let update = r#"{"arg":{"channel":"balance_and_position","uid":"77982378738415879"},"data":[{"pTime":"1597026383085","eventType":"filled","balData":[],"posData":[{"avgPx":"2570","ccy":"ETH","instId":"ETH-USD-SWAP","instType":"SWAP","mgnMode":"isolated","pos":"3","posId":"307173036051017730","posSide":"long","tradeId":"","uTime":"1614846244200"}],"trades":[]}]}"#;
+        portfolio.apply_push(&parse_push(update)).unwrap();
+        let position = portfolio.position("307173036051017730").unwrap();
+        assert_eq!(position.pos, dec("3"));
+        assert_eq!(position.lever, Some(dec("10")));
+
+        let stale = positions.replace(r#""pos":"1""#, r#""pos":"0""#);
+        portfolio.apply_push(&parse_push(&stale)).unwrap();
+        assert_eq!(portfolio.positions().count(), 1);
+
+        let closed = stale.replace("1614846244194", "1614846244300");
+        portfolio.apply_push(&parse_push(&closed)).unwrap();
+        assert_eq!(portfolio.positions().count(), 0);
+    }
+}
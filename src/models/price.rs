@@ -0,0 +1,72 @@
+use super::decimal::decimal_newtype;
+use super::Size;
+use rust_decimal::Decimal;
+
+/// Exact order book price, usable as an ordered map key
+///
+/// Prices are stored as decimals so that levels received from OKX compare
+/// exactly instead of relying on floating point equality, and print back with
+/// the scale they were received with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Price(Decimal);
+
+decimal_newtype!(Price);
+
+impl Price {
+    /// Returns `true` if the price is a whole multiple of `tick`
+    pub fn is_on_tick(&self, tick: Price) -> bool {
+        !tick.0.is_zero() && (self.0 % tick.0).is_zero()
+    }
+
+    /// Rounds down to the nearest multiple of `tick`
+    pub fn floor_to_tick(&self, tick: Price) -> Price {
+        if tick.0.is_zero() {
+            return *self;
+        }
+        Price((self.0 / tick.0).floor() * tick.0)
+    }
+
+    /// Returns the quote value of `size` at this price
+    pub fn notional(&self, size: Size) -> Decimal {
+        self.0 * size.as_decimal()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_price_round_trips_exchange_string() {
+        let price: Price = "41006.80".parse().unwrap();
+        assert_eq!(price.to_string(), "41006.80");
+        assert_eq!(serde_json::to_string(&price).unwrap(), r#""41006.80""#);
+        assert_eq!(price, "41006.8".parse().unwrap());
+        assert_eq!(price.to_f64(), 41006.8);
+        assert_eq!(f64::from(price), 41006.8);
+    }
+
+    #[test]
+    fn test_price_deserialize_string_and_number() {
+        let prices: Vec<Price> = serde_json::from_str(r#"["0.1", 0.1, 7]"#).unwrap();
+        assert_eq!(prices[0], prices[1]);
+        assert_eq!(prices[2], Price::new(Decimal::from(7)));
+        assert!(Price::try_from(f64::NAN).is_err());
+    }
+
+    #[test]
+    fn test_price_tick_helpers() {
+        let tick: Price = "0.1".parse().unwrap();
+        let price: Price = "41006.8".parse().unwrap();
+        assert!(price.is_on_tick(tick));
+        assert!(!"41006.85".parse::<Price>().unwrap().is_on_tick(tick));
+        assert_eq!(
+            "41006.85".parse::<Price>().unwrap().floor_to_tick(tick),
+            price
+        );
+        assert_eq!(
+            price.notional("0.5".parse().unwrap()).to_string(),
+            "20503.40"
+        );
+    }
+}
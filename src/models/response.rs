@@ -0,0 +1,85 @@
+use serde::Deserialize;
+
+/// Envelope wrapping every OKX REST response
+///
+/// `code` is `"0"` on success; otherwise `msg` describes the error and
+/// `data` is usually empty.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(bound(deserialize = "T: Deserialize<'de>"))]
+pub struct ApiResponse<T> {
+    pub code: String,
+    #[serde(default)]
+    pub msg: String,
+    #[serde(default = "Vec::new")]
+    pub data: Vec<T>,
+}
+
+impl<T> ApiResponse<T> {
+    pub fn is_success(&self) -> bool {
+        self.code == "0"
+    }
+}
+
+/// Broad category of an OKX error code, used to decide whether to retry
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ApiErrorKind {
+    /// Too many requests; retry after backing off
+    RateLimited,
+    /// Missing, invalid or expired credentials, signature or permissions
+    Auth,
+    /// A request parameter is missing or invalid
+    InvalidParameter,
+    /// OKX is busy or temporarily unavailable; retry after backing off
+    SystemBusy,
+    /// Any other error, such as a rejected order
+    Other,
+}
+
+impl ApiErrorKind {
+    /// Classifies an OKX error code
+    pub fn from_code(code: &str) -> Self {
+        match code {
+            "50011" | "50040" | "50061" => ApiErrorKind::RateLimited,
+            "50001" | "50004" | "50013" | "50026" => ApiErrorKind::SystemBusy,
+            "50030" | "50037" | "50038" => ApiErrorKind::Auth,
+            "50000" | "50002" | "50006" | "50014" | "50015" | "50016" | "51000" | "51001" => {
+                ApiErrorKind::InvalidParameter
+            }
+            code if code.starts_with("501") && code.len() == 5 => ApiErrorKind::Auth,
+            _ => ApiErrorKind::Other,
+        }
+    }
+
+    /// Returns `true` if the same request may succeed when sent again later
+    pub fn is_retryable(&self) -> bool {
+        matches!(self, ApiErrorKind::RateLimited | ApiErrorKind::SystemBusy)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_envelope_and_error_classification() {
+        let response: ApiResponse<serde_json::Value> = serde_json::from_str(
+            r#"{"code":"51001","msg":"Instrument ID does not exist","data":[]}"#,
+        )
+        .unwrap();
+        assert!(!response.is_success());
+        assert_eq!(
+            ApiErrorKind::from_code(&response.code),
+            ApiErrorKind::InvalidParameter
+        );
+
+        let response: ApiResponse<serde_json::Value> =
+            serde_json::from_str(r#"{"code":"50011","msg":"Too Many Requests"}"#).unwrap();
+        assert!(response.data.is_empty());
+
+        assert!(ApiErrorKind::from_code("50011").is_retryable());
+        assert!(ApiErrorKind::from_code("50013").is_retryable());
+        assert_eq!(ApiErrorKind::from_code("50113"), ApiErrorKind::Auth);
+        assert_eq!(ApiErrorKind::from_code("51008"), ApiErrorKind::Other);
+        assert!(!ApiErrorKind::from_code("50113").is_retryable());
+    }
+}
@@ -0,0 +1,43 @@
+use super::decimal::decimal_newtype;
+use rust_decimal::Decimal;
+
+/// Exact order book size, kept as received from OKX
+///
+/// The original scale is preserved so the size prints back exactly as the
+/// exchange sent it, which the book checksum depends on.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Size(Decimal);
+
+decimal_newtype!(Size);
+
+impl Size {
+    /// Returns `true` if the size is zero, which deletes a level
+    pub fn is_zero(&self) -> bool {
+        self.0.is_zero()
+    }
+
+    /// Returns `true` if the size is below zero, which OKX never sends
+    pub fn is_sign_negative(&self) -> bool {
+        self.0.is_sign_negative() && !self.0.is_zero()
+    }
+
+    /// Returns `true` if the size is a whole multiple of `lot`
+    pub fn is_multiple_of(&self, lot: Size) -> bool {
+        !lot.0.is_zero() && (self.0 % lot.0).is_zero()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_size_preserves_trailing_zeros() {
+        let size: Size = serde_json::from_str(r#""0.10000000""#).unwrap();
+        assert_eq!(size.to_string(), "0.10000000");
+        assert_eq!(serde_json::to_string(&size).unwrap(), r#""0.10000000""#);
+        assert!("0".parse::<Size>().unwrap().is_zero());
+        assert!(size.is_multiple_of("0.1".parse().unwrap()));
+        assert!(!size.is_multiple_of("0.3".parse().unwrap()));
+    }
+}
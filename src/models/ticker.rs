@@ -0,0 +1,85 @@
+use super::de::{empty_as_none, from_str};
+use super::{Price, Size};
+use rust_decimal::Decimal;
+use serde::Deserialize;
+
+/// Latest price, best bid/ask and 24-hour statistics of an instrument
+///
+/// Returned by the `ticker` and `tickers` REST endpoints and pushed on the
+/// `tickers` WebSocket channel. The best bid or ask is `None` while that
+/// side of the book is empty.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Ticker {
+    pub inst_type: String,
+    pub inst_id: String,
+    /// Price of the last trade
+    #[serde(default, deserialize_with = "empty_as_none")]
+    pub last: Option<Price>,
+    /// Size of the last trade
+    #[serde(default, deserialize_with = "empty_as_none")]
+    pub last_sz: Option<Size>,
+    #[serde(default, deserialize_with = "empty_as_none")]
+    pub ask_px: Option<Price>,
+    #[serde(default, deserialize_with = "empty_as_none")]
+    pub ask_sz: Option<Size>,
+    #[serde(default, deserialize_with = "empty_as_none")]
+    pub bid_px: Option<Price>,
+    #[serde(default, deserialize_with = "empty_as_none")]
+    pub bid_sz: Option<Size>,
+    /// Open price of the last 24 hours
+    #[serde(default, deserialize_with = "empty_as_none")]
+    pub open_24h: Option<Price>,
+    #[serde(default, deserialize_with = "empty_as_none")]
+    pub high_24h: Option<Price>,
+    #[serde(default, deserialize_with = "empty_as_none")]
+    pub low_24h: Option<Price>,
+    /// 24-hour volume in base currency (spot) or contracts (derivatives)
+    #[serde(default, deserialize_with = "empty_as_none")]
+    pub vol_24h: Option<Decimal>,
+    /// 24-hour volume in quote currency (spot) or base currency (derivatives)
+    #[serde(default, deserialize_with = "empty_as_none")]
+    pub vol_ccy_24h: Option<Decimal>,
+    /// Time of the data in milliseconds
+    #[serde(deserialize_with = "from_str")]
+    pub ts: u64,
+}
+
+impl Ticker {
+    /// Midpoint of the best bid and ask
+    pub fn mid_price(&self) -> Option<Decimal> {
+        let bid = self.bid_px?.as_decimal();
+        let ask = self.ask_px?.as_decimal();
+        Some((bid + ask) / Decimal::TWO)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ticker_deserialize() {
+        let ticker: Ticker = serde_json::from_str(
+            r#"{"instType":"SPOT","instId":"BTC-USDT","last":"9999.99","lastSz":"0.1",
+                "askPx":"10000","askSz":"11","bidPx":"9999.98","bidSz":"5",
+                "open24h":"9000","high24h":"10000","low24h":"8888.88",
+                "volCcy24h":"2222","vol24h":"2222","sodUtc0":"0.1","sodUtc8":"0.1",
+                "ts":"1597026383085"}"#,
+        )
+        .unwrap();
+        assert_eq!(ticker.last, Some("9999.99".parse().unwrap()));
+        assert_eq!(ticker.low_24h, Some("8888.88".parse().unwrap()));
+        assert_eq!(ticker.vol_ccy_24h, Some(Decimal::from(2222)));
+        assert_eq!(ticker.mid_price(), Some("9999.99".parse().unwrap()));
+        assert_eq!(ticker.ts, 1597026383085);
+
+        let empty_book: Ticker = serde_json::from_str(
+            r#"{"instType":"SPOT","instId":"NEW-USDT","last":"1","lastSz":"1","askPx":"","askSz":"",
+                "bidPx":"","bidSz":"","ts":"1597026383085"}"#,
+        )
+        .unwrap();
+        assert_eq!(empty_book.ask_px, None);
+        assert_eq!(empty_book.mid_price(), None);
+    }
+}
@@ -0,0 +1,81 @@
+use super::de::from_str;
+use super::{OrderSide, Price, Size};
+use serde::Deserialize;
+
+/// A public trade print
+///
+/// Returned by the `trades` and `history-trades` REST endpoints and pushed
+/// on the `trades` and `trades-all` WebSocket channels.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Trade {
+    pub inst_id: String,
+    pub trade_id: String,
+    pub px: Price,
+    pub sz: Size,
+    /// Side of the taker
+    pub side: OrderSide,
+    /// Time of the trade in milliseconds
+    #[serde(deserialize_with = "from_str")]
+    pub ts: u64,
+    /// Number of trades aggregated into this print, only above 1 on the `trades` channel
+    #[serde(default = "one", deserialize_with = "from_str")]
+    pub count: u64,
+}
+
+fn one() -> u64 {
+    1
+}
+
+/// Where a page of `history-trades` ends
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TradeCursor {
+    /// Trades older than this trade ID
+    TradeId(String),
+    /// Trades older than this time in milliseconds
+    Timestamp(u64),
+}
+
+impl TradeCursor {
+    /// The `type` parameter OKX pages `history-trades` by
+    pub(crate) fn kind(&self) -> &'static str {
+        match self {
+            TradeCursor::TradeId(_) => "1",
+            TradeCursor::Timestamp(_) => "2",
+        }
+    }
+
+    pub(crate) fn value(&self) -> String {
+        match self {
+            TradeCursor::TradeId(trade_id) => trade_id.clone(),
+            TradeCursor::Timestamp(ts) => ts.to_string(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::ws_message::parse_push;
+
+    #[test]
+    fn test_trade_deserialize_from_rest_and_push() {
+        let trade: Trade = serde_json::from_str(
+            r#"{"instId":"BTC-USDT","side":"sell","sz":"0.00001","px":"29963.2","source":"0",
+                "tradeId":"242720720","ts":"1654161646974"}"#,
+        )
+        .unwrap();
+        assert_eq!(trade.side, OrderSide::Sell);
+        assert_eq!(trade.px, "29963.2".parse().unwrap());
+        assert_eq!(trade.count, 1);
+
+        let push = parse_push(
+            r#"{"arg":{"channel":"trades","instId":"BTC-USDT"},"data":[{"instId":"BTC-USDT",
+                "tradeId":"130639474","px":"42219.9","sz":"0.12060306","side":"buy",
+                "ts":"1630048897897","count":"3"}]}"#,
+        );
+        let trades = push.decode::<Trade>().unwrap();
+        assert_eq!(trades[0].count, 3);
+        assert_eq!(trades[0].ts, 1630048897897);
+    }
+}
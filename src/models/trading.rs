@@ -0,0 +1,253 @@
+use super::de::empty_as_none;
+use super::{OrderSide, Price, Size};
+use serde::{Deserialize, Serialize};
+
+/// Parameters of a new order
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OrderRequest {
+    pub inst_id: String,
+    /// `cash` for spot, `cross` or `isolated` for margin and derivatives
+    pub td_mode: String,
+    pub side: OrderSide,
+    /// `market`, `limit`, `post_only`, `fok`, `ioc`, ...
+    pub ord_type: String,
+    pub sz: Size,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub px: Option<Price>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cl_ord_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tag: Option<String>,
+    /// `long` or `short` in long/short position mode
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pos_side: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reduce_only: Option<bool>,
+}
+
+impl OrderRequest {
+    /// Creates a spot limit order
+    pub fn limit(inst_id: &str, side: OrderSide, px: Price, sz: Size) -> Self {
+        Self::new(inst_id, side, "limit", Some(px), sz)
+    }
+
+    /// Creates a spot market order
+    pub fn market(inst_id: &str, side: OrderSide, sz: Size) -> Self {
+        Self::new(inst_id, side, "market", None, sz)
+    }
+
+    fn new(inst_id: &str, side: OrderSide, ord_type: &str, px: Option<Price>, sz: Size) -> Self {
+        OrderRequest {
+            inst_id: inst_id.to_string(),
+            td_mode: "cash".to_string(),
+            side,
+            ord_type: ord_type.to_string(),
+            sz,
+            px,
+            cl_ord_id: None,
+            tag: None,
+            pos_side: None,
+            reduce_only: None,
+        }
+    }
+
+    pub fn with_td_mode(mut self, td_mode: &str) -> Self {
+        self.td_mode = td_mode.to_string();
+        self
+    }
+
+    pub fn with_ord_type(mut self, ord_type: &str) -> Self {
+        self.ord_type = ord_type.to_string();
+        self
+    }
+
+    pub fn with_cl_ord_id(mut self, cl_ord_id: &str) -> Self {
+        self.cl_ord_id = Some(cl_ord_id.to_string());
+        self
+    }
+
+    pub fn with_pos_side(mut self, pos_side: &str) -> Self {
+        self.pos_side = Some(pos_side.to_string());
+        self
+    }
+
+    pub fn with_reduce_only(mut self, reduce_only: bool) -> Self {
+        self.reduce_only = Some(reduce_only);
+        self
+    }
+}
+
+/// Identifies an order to cancel
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CancelRequest {
+    pub inst_id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ord_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cl_ord_id: Option<String>,
+}
+
+impl CancelRequest {
+    pub fn by_ord_id(inst_id: &str, ord_id: &str) -> Self {
+        CancelRequest {
+            inst_id: inst_id.to_string(),
+            ord_id: Some(ord_id.to_string()),
+            cl_ord_id: None,
+        }
+    }
+
+    pub fn by_cl_ord_id(inst_id: &str, cl_ord_id: &str) -> Self {
+        CancelRequest {
+            inst_id: inst_id.to_string(),
+            ord_id: None,
+            cl_ord_id: Some(cl_ord_id.to_string()),
+        }
+    }
+}
+
+/// New price and/or size for a live order
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AmendRequest {
+    pub inst_id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ord_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cl_ord_id: Option<String>,
+    /// Client-supplied ID of the amendment itself
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub req_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub new_px: Option<Price>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub new_sz: Option<Size>,
+    /// Cancel the order if the amendment fails
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cxl_on_fail: Option<bool>,
+}
+
+impl AmendRequest {
+    pub fn by_ord_id(inst_id: &str, ord_id: &str) -> Self {
+        AmendRequest {
+            inst_id: inst_id.to_string(),
+            ord_id: Some(ord_id.to_string()),
+            cl_ord_id: None,
+            req_id: None,
+            new_px: None,
+            new_sz: None,
+            cxl_on_fail: None,
+        }
+    }
+
+    pub fn by_cl_ord_id(inst_id: &str, cl_ord_id: &str) -> Self {
+        AmendRequest {
+            ord_id: None,
+            cl_ord_id: Some(cl_ord_id.to_string()),
+            ..AmendRequest::by_ord_id(inst_id, "")
+        }
+    }
+
+    pub fn with_new_px(mut self, new_px: Price) -> Self {
+        self.new_px = Some(new_px);
+        self
+    }
+
+    pub fn with_new_sz(mut self, new_sz: Size) -> Self {
+        self.new_sz = Some(new_sz);
+        self
+    }
+
+    pub fn with_cxl_on_fail(mut self, cxl_on_fail: bool) -> Self {
+        self.cxl_on_fail = Some(cxl_on_fail);
+        self
+    }
+}
+
+/// Cancels all market maker protection orders of an instrument family
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MassCancelRequest {
+    /// Only `OPTION` is supported by OKX
+    pub inst_type: String,
+    pub inst_family: String,
+}
+
+impl MassCancelRequest {
+    pub fn new(inst_type: &str, inst_family: &str) -> Self {
+        MassCancelRequest {
+            inst_type: inst_type.to_string(),
+            inst_family: inst_family.to_string(),
+        }
+    }
+}
+
+/// Per-order outcome of a place, cancel or amend request
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OrderAck {
+    #[serde(default)]
+    pub ord_id: String,
+    #[serde(default, deserialize_with = "empty_as_none")]
+    pub cl_ord_id: Option<String>,
+    #[serde(default, deserialize_with = "empty_as_none")]
+    pub req_id: Option<String>,
+    #[serde(default, deserialize_with = "empty_as_none")]
+    pub tag: Option<String>,
+    /// Time the request was processed by the matching engine
+    #[serde(default, deserialize_with = "empty_as_none")]
+    pub ts: Option<u64>,
+    /// `"0"` on success
+    pub s_code: String,
+    pub s_msg: String,
+}
+
+impl OrderAck {
+    pub fn is_success(&self) -> bool {
+        self.s_code == "0"
+    }
+}
+
+/// Outcome of a `mass-cancel` request
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct MassCancelAck {
+    pub result: bool,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_request_serialization_and_ack_decoding() {
+        let order = OrderRequest::limit(
+            "BTC-USDT",
+            OrderSide::Buy,
+            "2.15".parse().unwrap(),
+            "2".parse().unwrap(),
+        )
+        .with_cl_ord_id("b15");
+        assert_eq!(
+            serde_json::to_string(&order).unwrap(),
+            r#"{"instId":"BTC-USDT","tdMode":"cash","side":"buy","ordType":"limit","sz":"2","px":"2.15","clOrdId":"b15"}"#
+        );
+
+        let market = OrderRequest::market("BTC-USDT", OrderSide::Sell, "1".parse().unwrap());
+        assert_eq!(serde_json::to_value(&market).unwrap().get("px"), None);
+
+        let amend = AmendRequest::by_cl_ord_id("BTC-USDT", "b15").with_new_sz("3".parse().unwrap());
+        assert_eq!(
+            serde_json::to_string(&amend).unwrap(),
+            r#"{"instId":"BTC-USDT","clOrdId":"b15","newSz":"3"}"#
+        );
+
+        let ack: OrderAck = serde_json::from_str(
+            r#"{"clOrdId":"","ordId":"12345689","tag":"","ts":"1695190491421339","sCode":"0","sMsg":""}"#,
+        )
+        .unwrap();
+        assert!(ack.is_success());
+        assert_eq!(ack.cl_ord_id, None);
+        assert_eq!(ack.ts, Some(1695190491421339));
+    }
+}
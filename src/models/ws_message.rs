@@ -0,0 +1,252 @@
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// Identifies a WebSocket channel subscription, as sent in `args` and echoed in `arg`
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChannelArg {
+    /// Channel name, e.g. `books` or `tickers`
+    pub channel: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub inst_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub inst_type: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub inst_family: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ccy: Option<String>,
+}
+
+impl ChannelArg {
+    /// Creates an argument for a channel without instrument filters
+    pub fn new(channel: &str) -> Self {
+        ChannelArg {
+            channel: channel.to_string(),
+            ..Default::default()
+        }
+    }
+
+    /// Creates an argument for a channel on a single instrument
+    pub fn instrument(channel: &str, inst_id: &str) -> Self {
+        ChannelArg {
+            inst_id: Some(inst_id.to_string()),
+            ..ChannelArg::new(channel)
+        }
+    }
+
+    /// Creates an argument for a channel on all instruments of a type, e.g. `SPOT` or `ANY`
+    pub fn instrument_type(channel: &str, inst_type: &str) -> Self {
+        ChannelArg {
+            inst_type: Some(inst_type.to_string()),
+            ..ChannelArg::new(channel)
+        }
+    }
+}
+
+/// Operational event sent by OKX in reply to requests or on its own
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(
+    tag = "event",
+    rename_all = "kebab-case",
+    rename_all_fields = "camelCase"
+)]
+pub enum WsEvent {
+    /// A channel subscription was accepted
+    Subscribe {
+        arg: ChannelArg,
+        /// Request `id`, echoed when the request carried one
+        id: Option<String>,
+        conn_id: Option<String>,
+    },
+    /// A channel subscription was removed
+    Unsubscribe {
+        arg: ChannelArg,
+        id: Option<String>,
+        conn_id: Option<String>,
+    },
+    /// A request was rejected
+    Error {
+        code: String,
+        msg: String,
+        id: Option<String>,
+        conn_id: Option<String>,
+    },
+    /// Reply to a `login` request; `code` is `"0"` on success
+    Login {
+        code: String,
+        msg: String,
+        conn_id: Option<String>,
+    },
+    /// Service notice, e.g. an upcoming disconnection for maintenance
+    Notice {
+        code: String,
+        msg: String,
+        conn_id: Option<String>,
+    },
+    /// Number of connections currently subscribed to a private channel
+    ChannelConnCount {
+        channel: String,
+        conn_count: String,
+        conn_id: Option<String>,
+    },
+    /// A private channel subscription exceeded the connection limit
+    ChannelConnCountError {
+        channel: String,
+        code: String,
+        msg: String,
+        conn_id: Option<String>,
+    },
+    /// An event type this crate does not know about yet
+    #[serde(other)]
+    Unknown,
+}
+
+/// Data pushed on a subscribed channel
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct WsPush {
+    /// The subscription the data belongs to
+    pub arg: ChannelArg,
+    /// `snapshot` or `update` for book channels, absent elsewhere
+    pub action: Option<String>,
+    /// Raw channel payload, decoded with [`WsPush::decode`]
+    pub data: Value,
+}
+
+impl WsPush {
+    /// Decodes the `data` array into channel-specific records
+    pub fn decode<T: DeserializeOwned>(&self) -> Result<Vec<T>, serde_json::Error> {
+        Vec::<T>::deserialize(&self.data)
+    }
+}
+
+/// Reply to a request sent with an `id`, such as an order operation
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WsResponse {
+    /// The `id` of the request
+    pub id: String,
+    /// The operation, e.g. `order` or `cancel-order`
+    pub op: String,
+    /// `"0"` on success, `"1"` if all items failed, `"2"` if some failed in a batch
+    pub code: String,
+    pub msg: String,
+    /// Per-item results, decoded with [`WsResponse::decode`]
+    #[serde(default)]
+    pub data: Value,
+    /// Time the request reached the gateway, in microseconds
+    pub in_time: Option<String>,
+    /// Time the response left the gateway, in microseconds
+    pub out_time: Option<String>,
+}
+
+impl WsResponse {
+    /// Decodes the `data` array into operation-specific records
+    pub fn decode<T: DeserializeOwned>(&self) -> Result<Vec<T>, serde_json::Error> {
+        Vec::<T>::deserialize(&self.data)
+    }
+}
+
+/// Inbound WebSocket message
+#[derive(Debug, Clone, PartialEq)]
+pub enum WsMessage {
+    /// Operational event such as a subscription acknowledgement or an error
+    Event(WsEvent),
+    /// Channel data push
+    Push(WsPush),
+    /// Reply to an operation such as placing or canceling an order
+    Response(WsResponse),
+    /// Generated by the client after it re-established a dropped connection
+    /// and replayed its subscriptions; state built from earlier messages
+    /// should be discarded
+    Reconnected {
+        /// Connection attempts it took to reconnect
+        attempts: u32,
+    },
+    /// Text frame that could not be decoded, e.g. an event or schema this
+    /// crate does not know about yet, kept as received
+    Unknown(String),
+}
+
+impl WsMessage {
+    /// Parses a text frame received from OKX
+    pub fn parse(text: &str) -> Result<Self, serde_json::Error> {
+        let value: Value = serde_json::from_str(text)?;
+        if value.get("event").is_some() {
+            Ok(WsMessage::Event(WsEvent::deserialize(value)?))
+        } else if value.get("op").is_some() {
+            Ok(WsMessage::Response(WsResponse::deserialize(value)?))
+        } else {
+            Ok(WsMessage::Push(WsPush::deserialize(value)?))
+        }
+    }
+}
+
+/// Parses a text frame that must be a channel push
+#[cfg(test)]
+pub(crate) fn parse_push(text: &str) -> WsPush {
+    let WsMessage::Push(push) = WsMessage::parse(text).unwrap() else {
+        panic!("expected a push");
+    };
+    push
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_events() {
+        let text = r#"{"event":"subscribe","arg":{"channel":"books","instId":"BTC-USDT"},"connId":"a4d3ae55"}"#;
+        assert_eq!(
+            WsMessage::parse(text).unwrap(),
+            WsMessage::Event(WsEvent::Subscribe {
+                arg: ChannelArg::instrument("books", "BTC-USDT"),
+                id: None,
+                conn_id: Some("a4d3ae55".into()),
+            })
+        );
+
+        let text = r#"{"id":"7","event":"error","code":"60012","msg":"Invalid request","connId":"a4d3ae55"}"#;
+        assert!(matches!(
+            WsMessage::parse(text).unwrap(),
+            WsMessage::Event(WsEvent::Error { code, id, .. }) if code == "60012" && id.as_deref() == Some("7")
+        ));
+
+        let text = r#"{"event":"channel-conn-count","channel":"orders","connCount":"2","connId":"abcd1234"}"#;
+        assert!(matches!(
+            WsMessage::parse(text).unwrap(),
+            WsMessage::Event(WsEvent::ChannelConnCount { conn_count, .. }) if conn_count == "2"
+        ));
+
+        let text = r#"{"event":"something-new","connId":"abcd1234"}"#;
+        assert_eq!(
+            WsMessage::parse(text).unwrap(),
+            WsMessage::Event(WsEvent::Unknown)
+        );
+    }
+
+    #[test]
+    fn test_parse_push() {
+        let text = r#"{"arg":{"channel":"books","instId":"BTC-USDT"},"action":"update","data":[{"asks":[],"bids":[],"ts":"1597026383085"}]}"#;
+        let WsMessage::Push(push) = WsMessage::parse(text).unwrap() else {
+            panic!("expected a push");
+        };
+        assert_eq!(push.arg, ChannelArg::instrument("books", "BTC-USDT"));
+        assert_eq!(push.action.as_deref(), Some("update"));
+        let data: Vec<Value> = push.decode().unwrap();
+        assert_eq!(data[0]["ts"], "1597026383085");
+    }
+
+    #[test]
+    fn test_parse_response() {
+        let text = r#"{"id":"1512","op":"order","data":[{"clOrdId":"","ordId":"","tag":"","ts":"1695190491421339","sCode":"51008","sMsg":"Order failed. Insufficient balance."}],"code":"1","msg":"","inTime":"1695190491421339","outTime":"1695190491423240"}"#;
+        let WsMessage::Response(response) = WsMessage::parse(text).unwrap() else {
+            panic!("expected a response");
+        };
+        assert_eq!(response.id, "1512");
+        assert_eq!(response.code, "1");
+        let data: Vec<Value> = response.decode().unwrap();
+        assert_eq!(data[0]["sCode"], "51008");
+    }
+}
@@ -1,4 +1,4 @@
-use crate::models::Orderbook;
+use crate::models::{Level, Orderbook};
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
@@ -11,8 +11,8 @@ struct OrderbookResponse {
 
 #[derive(Debug, Serialize, Deserialize)]
 struct OrderbookData {
-    asks: Vec<[String; 2]>,
-    bids: Vec<[String; 2]>,
+    asks: Vec<Level>,
+    bids: Vec<Level>,
     ts: String,
 }
 
@@ -33,21 +33,16 @@ pub fn parse_order_book(data: &str) -> Result<Orderbook, OrderbookError> {
 
     let orderbook_data = response
         .data
-        .first()
+        .into_iter()
+        .next()
         .ok_or_else(|| OrderbookError::InvalidData("Empty 'data' array".into()))?;
 
-    Ok(Orderbook {
-        asks: parse_orders(&orderbook_data.asks)?,
-        bids: parse_orders(&orderbook_data.bids)?,
-        ts: orderbook_data.ts.parse::<u64>()?,
-    })
-}
-
-fn parse_orders(orders: &[[String; 2]]) -> Result<Vec<(f64, f64)>, OrderbookError> {
-    orders
-        .iter()
-        .map(|[price, amount]| Ok((price.parse::<f64>()?, amount.parse::<f64>()?)))
-        .collect()
+    Orderbook::new(
+        orderbook_data.asks,
+        orderbook_data.bids,
+        orderbook_data.ts.parse::<u64>()?,
+    )
+    .map_err(|e| OrderbookError::InvalidData(e.to_string()))
 }
 
 pub fn validate_order_book_data(data: &str) -> Result<(), OrderbookError> {
@@ -86,9 +81,19 @@ mod tests {
         let orderbook = orderbook.unwrap();
         assert_eq!(orderbook.asks.len(), 1);
         assert_eq!(orderbook.bids.len(), 1);
-        assert_eq!(orderbook.asks[0], (41006.8, 0.60030921));
-        assert_eq!(orderbook.bids[0], (41006.3, 0.30178210));
+        assert_eq!(
+            orderbook.best_ask(),
+            Some(("41006.8".parse().unwrap(), "0.60030921".parse().unwrap()))
+        );
+        assert_eq!(
+            orderbook.best_bid(),
+            Some(("41006.3".parse().unwrap(), "0.30178210".parse().unwrap()))
+        );
         assert_eq!(orderbook.ts, 1621447077008);
+        assert_eq!(
+            serde_json::to_string(&orderbook).unwrap(),
+            r#"{"asks":[["41006.8","0.60030921","0","0"]],"bids":[["41006.3","0.30178210","0","0"]],"ts":1621447077008}"#
+        );
     }
 
     #[test]